@@ -4,5 +4,7 @@ use crate::{traits_mod::create_traits::Create, types_mod::result_types::Res};
 
 #[async_trait::async_trait]
 pub trait AiModule: Send + Sync + Create + Debug {
-    async fn process(&self, weather: String) -> Res<String>;
+    async fn process(&self, prompt: String) -> Res<String>;
+    /// Name of the model, which generates responses.
+    fn model(&self) -> String;
 }
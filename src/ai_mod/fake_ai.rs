@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use crate::{
+    ai_mod::ai::AiModule, traits_mod::create_traits::Create, types_mod::result_types::Res,
+};
+
+/// AI for tests, which always responds with the same text.
+#[derive(Debug)]
+pub struct FakeAi {
+    response: String,
+}
+
+impl FakeAi {
+    #[tracing::instrument]
+    pub fn with_response(response: &str) -> Self {
+        Self {
+            response: response.into(),
+        }
+    }
+}
+
+impl Create for FakeAi {
+    #[tracing::instrument]
+    fn new() -> Res<Arc<Self>> {
+        Ok(Arc::new(Self::with_response("Хорошего дня!")))
+    }
+}
+
+#[async_trait::async_trait]
+impl AiModule for FakeAi {
+    async fn process(&self, _prompt: String) -> Res<String> {
+        Ok(self.response.clone())
+    }
+
+    fn model(&self) -> String {
+        "fake".into()
+    }
+}
@@ -1,2 +1,4 @@
 pub mod ai;
+#[cfg(test)]
+pub mod fake_ai;
 pub mod ollama_ai;
@@ -2,7 +2,6 @@ use std::sync::Arc;
 
 use ollama_rs::{Ollama, generation::completion::request::GenerationRequest};
 use regex::Regex;
-use string_format::string_format;
 
 use crate::{
     ai_mod::ai::AiModule, tools_mod::config_tools::CONFIG, traits_mod::create_traits::Create,
@@ -30,19 +29,20 @@ impl Create for OllamaAi {
 #[async_trait::async_trait]
 impl AiModule for OllamaAi {
     #[tracing::instrument]
-    async fn process(&self, weather: String) -> Res<String> {
+    async fn process(&self, prompt: String) -> Res<String> {
         // Note: it is normal to create ollama every function call, because it just has an address to requests
         let ollama = Ollama::default();
         let res = ollama
-            .generate(GenerationRequest::new(
-                CONFIG.ai_model.clone(),
-                string_format(CONFIG.ai_prompt.clone(), weather),
-            ))
+            .generate(GenerationRequest::new(CONFIG.ai_model.clone(), prompt))
             .await?
             .response;
         let fmt_res = Self::remove_reasoning(&res);
         Ok(fmt_res)
     }
+
+    fn model(&self) -> String {
+        CONFIG.ai_model.clone()
+    }
 }
 
 #[cfg(test)]
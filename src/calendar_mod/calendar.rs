@@ -0,0 +1,10 @@
+use std::fmt::Debug;
+
+use chrono::NaiveDate;
+
+use crate::{models_mod::occasion_model::OccasionModel, traits_mod::create_traits::Create};
+
+pub trait CalendarModule: Send + Sync + Create + Debug {
+    /// Returns occasions of the date, both common and specific for the chat.
+    fn occasions(&self, date: NaiveDate, chat_id: &str) -> Vec<OccasionModel>;
+}
@@ -0,0 +1,269 @@
+use std::{fs, path::Path, sync::Arc};
+
+use chrono::{Datelike, NaiveDate};
+use config::{File, FileFormat};
+use serde::Deserialize;
+
+use crate::{
+    calendar_mod::calendar::CalendarModule, models_mod::occasion_model::OccasionModel,
+    tools_mod::config_tools::CONFIG, traits_mod::create_traits::Create,
+    types_mod::result_types::Res,
+};
+
+#[derive(Deserialize, Debug)]
+struct TomlCalendar {
+    occasions: Vec<OccasionModel>,
+}
+
+/// Calendar, which is loaded from `.toml` or `.ics` file once on start.
+#[derive(Debug, Default)]
+pub struct FileCalendar {
+    occasions: Vec<OccasionModel>,
+}
+
+impl FileCalendar {
+    #[tracing::instrument(skip(text))]
+    pub fn from_toml(text: &str) -> Res<Self> {
+        let calendar: TomlCalendar = config::Config::builder()
+            .add_source(File::from_str(text, FileFormat::Toml))
+            .build()?
+            .try_deserialize()?;
+        Ok(Self {
+            occasions: calendar.occasions,
+        })
+    }
+
+    /// Parses `VEVENT`s of iCalendar. Events with `RRULE:FREQ=YEARLY` repeat every year,
+    /// events with other rules are skipped, `X-GREETING-CHAT` property limits event to one chat.
+    #[tracing::instrument(skip(text))]
+    pub fn from_ics(text: &str) -> Res<Self> {
+        let unfolded = text
+            .replace("\r\n", "\n")
+            .replace("\n ", "")
+            .replace("\n\t", "");
+
+        let mut occasions = vec![];
+        let mut name = None;
+        let mut date: Option<NaiveDate> = None;
+        let mut rule = None;
+        let mut chat = None;
+
+        for line in unfolded.lines() {
+            let Some((prop, value)) = line.split_once(':') else {
+                continue;
+            };
+            // Parameters like `DTSTART;VALUE=DATE` are not needed
+            let prop = prop.split(';').next().unwrap_or_default();
+
+            match prop {
+                "BEGIN" if value == "VEVENT" => {
+                    name = None;
+                    date = None;
+                    rule = None;
+                    chat = None;
+                }
+                "SUMMARY" => name = Some(unescape_ics(value)),
+                "DTSTART" => {
+                    let day = value.get(..8).ok_or("Bad DTSTART in calendar")?;
+                    date = Some(NaiveDate::parse_from_str(day, "%Y%m%d")?);
+                }
+                "RRULE" => rule = Some(value.to_string()),
+                "X-GREETING-CHAT" => chat = Some(value.to_string()),
+                "END" if value == "VEVENT" => {
+                    if let (Some(name), Some(date)) = (name.take(), date.take()) {
+                        let is_yearly = match rule.take() {
+                            None => false,
+                            Some(rule) if is_yearly_rule(&rule, date) => true,
+                            Some(rule) => {
+                                tracing::warn!("Skipped `{name}` with unsupported RRULE `{rule}`");
+                                continue;
+                            }
+                        };
+                        occasions.push(OccasionModel {
+                            name,
+                            month: date.month(),
+                            day: date.day(),
+                            year: (!is_yearly).then_some(date.year()),
+                            chat: chat.take(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self { occasions })
+    }
+}
+
+/// Whether the rule repeats the event every year on the date of its start.
+fn is_yearly_rule(rule: &str, start: NaiveDate) -> bool {
+    let mut is_yearly = false;
+    for part in rule.split(';') {
+        match part.split_once('=') {
+            Some(("FREQ", "YEARLY")) => is_yearly = true,
+            Some(("INTERVAL", "1") | ("WKST", _)) => {}
+            Some(("BYMONTH", month)) if month.parse() == Ok(start.month()) => {}
+            Some(("BYMONTHDAY", day)) if day.parse() == Ok(start.day()) => {}
+            _ => return false,
+        }
+    }
+    is_yearly
+}
+
+fn unescape_ics(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+impl Create for FileCalendar {
+    #[tracing::instrument]
+    fn new() -> Res<Arc<Self>> {
+        if CONFIG.calendar_path.is_empty() {
+            return Ok(Arc::new(Self::default()));
+        }
+
+        let path = Path::new(&CONFIG.calendar_path);
+        let text = fs::read_to_string(path)?;
+        let calendar = if path.extension().is_some_and(|ext| ext == "ics") {
+            Self::from_ics(&text)?
+        } else {
+            Self::from_toml(&text)?
+        };
+        Ok(Arc::new(calendar))
+    }
+}
+
+impl CalendarModule for FileCalendar {
+    #[tracing::instrument]
+    fn occasions(&self, date: NaiveDate, chat_id: &str) -> Vec<OccasionModel> {
+        self.occasions
+            .iter()
+            .filter(|occasion| occasion.matches(date, chat_id))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod calendar_tests {
+    use chrono::NaiveDate;
+
+    use crate::calendar_mod::{calendar::CalendarModule, file_calendar::FileCalendar};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).expect("Failed to create date")
+    }
+
+    fn names(calendar: &FileCalendar, date: NaiveDate, chat_id: &str) -> Vec<String> {
+        calendar
+            .occasions(date, chat_id)
+            .into_iter()
+            .map(|occasion| occasion.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_toml_calendar() {
+        let calendar = FileCalendar::from_toml(
+            r#"
+            [[occasions]]
+            name = "Новый год"
+            month = 1
+            day = 1
+
+            [[occasions]]
+            name = "Годовщина канала"
+            month = 1
+            day = 1
+            chat = "-100"
+
+            [[occasions]]
+            name = "Запуск бота"
+            month = 9
+            day = 1
+            year = 2025
+            "#,
+        )
+        .expect("Failed to parse calendar");
+
+        assert_eq!(names(&calendar, date(2026, 1, 1), "1"), ["Новый год"]);
+        assert_eq!(
+            names(&calendar, date(2030, 1, 1), "-100"),
+            ["Новый год", "Годовщина канала"]
+        );
+        assert_eq!(names(&calendar, date(2025, 9, 1), "1"), ["Запуск бота"]);
+        assert!(names(&calendar, date(2026, 9, 1), "1").is_empty());
+        assert!(names(&calendar, date(2026, 1, 2), "-100").is_empty());
+    }
+
+    #[test]
+    fn test_ics_calendar() {
+        let calendar = FileCalendar::from_ics(
+            "BEGIN:VCALENDAR\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Международный женский\r\n  день\r\n\
+             DTSTART;VALUE=DATE:20260308\r\n\
+             RRULE:FREQ=YEARLY\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Стрим\\, посвящённый релизу\r\n\
+             DTSTART:20261019T150000Z\r\n\
+             X-GREETING-CHAT:-100\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n",
+        )
+        .expect("Failed to parse calendar");
+
+        assert_eq!(
+            names(&calendar, date(2027, 3, 8), "1"),
+            ["Международный женский день"]
+        );
+        assert_eq!(
+            names(&calendar, date(2026, 10, 19), "-100"),
+            ["Стрим, посвящённый релизу"]
+        );
+        assert!(names(&calendar, date(2026, 10, 19), "1").is_empty());
+        assert!(names(&calendar, date(2027, 10, 19), "-100").is_empty());
+    }
+
+    #[test]
+    fn test_ics_calendar_rules() {
+        let calendar = FileCalendar::from_ics(
+            "BEGIN:VEVENT\n\
+             SUMMARY:Планёрка\n\
+             DTSTART:20261019\n\
+             RRULE:FREQ=WEEKLY;BYDAY=MO\n\
+             END:VEVENT\n\
+             BEGIN:VEVENT\n\
+             SUMMARY:День рождения канала\n\
+             DTSTART:20240229\n\
+             RRULE:FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=29\n\
+             END:VEVENT\n",
+        )
+        .expect("Failed to parse calendar");
+
+        // Weekly event isn't supported, so it isn't shown even once
+        assert!(names(&calendar, date(2026, 10, 19), "1").is_empty());
+        assert_eq!(
+            names(&calendar, date(2026, 2, 28), "1"),
+            ["День рождения канала"]
+        );
+        assert_eq!(
+            names(&calendar, date(2028, 2, 29), "1"),
+            ["День рождения канала"]
+        );
+        assert!(names(&calendar, date(2028, 2, 28), "1").is_empty());
+    }
+
+    #[test]
+    fn test_ics_calendar_bad_date() {
+        assert!(
+            FileCalendar::from_ics("BEGIN:VEVENT\nSUMMARY:x\nDTSTART:2026\nEND:VEVENT").is_err()
+        );
+    }
+}
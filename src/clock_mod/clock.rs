@@ -0,0 +1,12 @@
+use std::fmt::Debug;
+
+use chrono::{DateTime, Utc};
+
+use crate::traits_mod::create_traits::Create;
+
+#[async_trait::async_trait]
+pub trait Clock: Send + Sync + Create + Debug {
+    fn now(&self) -> DateTime<Utc>;
+    /// Waits until the clock shows `at`, returns at once if this time has passed.
+    async fn sleep_until(&self, at: DateTime<Utc>);
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use tokio::sync::watch;
+
+use crate::{
+    clock_mod::clock::Clock, traits_mod::create_traits::Create, types_mod::result_types::Res,
+};
+
+/// Clock for tests, which shows the time it's set to and wakes sleepers when it's moved forward.
+#[derive(Debug)]
+pub struct ManualClock {
+    now: watch::Sender<DateTime<Utc>>,
+}
+
+impl ManualClock {
+    #[tracing::instrument]
+    pub fn at(now: DateTime<Utc>) -> Self {
+        Self {
+            now: watch::Sender::new(now),
+        }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.now.send_replace(now);
+    }
+
+    pub fn advance(&self, delta: TimeDelta) {
+        self.now.send_modify(|now| *now += delta);
+    }
+}
+
+impl Create for ManualClock {
+    #[tracing::instrument]
+    fn new() -> Res<Arc<Self>> {
+        Ok(Arc::new(Self::at(DateTime::UNIX_EPOCH)))
+    }
+}
+
+#[async_trait::async_trait]
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.borrow()
+    }
+
+    #[tracing::instrument]
+    async fn sleep_until(&self, at: DateTime<Utc>) {
+        // Sender lives as long as the clock, so waiting can't fail
+        let _ = self.now.subscribe().wait_for(|now| *now >= at).await;
+    }
+}
+
+#[cfg(test)]
+mod manual_clock_tests {
+    use std::{sync::Arc, time::Duration};
+
+    use chrono::{DateTime, TimeDelta};
+
+    use crate::clock_mod::{clock::Clock, manual_clock::ManualClock};
+
+    #[tokio::test]
+    async fn test_manual_clock() {
+        let start = DateTime::UNIX_EPOCH;
+        let clock = Arc::new(ManualClock::at(start));
+        assert_eq!(clock.now(), start);
+        clock.advance(TimeDelta::hours(1));
+        assert_eq!(clock.now(), start + TimeDelta::hours(1));
+
+        // Past time doesn't wait
+        clock.sleep_until(start).await;
+
+        let sleeper = tokio::spawn({
+            let clock = clock.clone();
+            async move { clock.sleep_until(start + TimeDelta::days(1)).await }
+        });
+        clock.advance(TimeDelta::hours(1));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!sleeper.is_finished());
+
+        clock.set(start + TimeDelta::days(2));
+        tokio::time::timeout(Duration::from_secs(1), sleeper)
+            .await
+            .expect("Sleeper wasn't woken")
+            .expect("Sleeper failed");
+    }
+}
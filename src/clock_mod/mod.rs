@@ -0,0 +1,4 @@
+pub mod clock;
+#[cfg(test)]
+pub mod manual_clock;
+pub mod system_clock;
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    clock_mod::clock::Clock, traits_mod::create_traits::Create, types_mod::result_types::Res,
+};
+
+/// Wall clock of the system.
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Create for SystemClock {
+    #[tracing::instrument]
+    fn new() -> Res<Arc<Self>> {
+        Ok(Arc::new(Self))
+    }
+}
+
+#[async_trait::async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    #[tracing::instrument]
+    async fn sleep_until(&self, at: DateTime<Utc>) {
+        // Wall time is checked again, because sleep doesn't count time when the system is suspended
+        while let Ok(wait) = (at - Utc::now()).to_std()
+            && !wait.is_zero()
+        {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
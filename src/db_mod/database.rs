@@ -1,12 +1,71 @@
 use std::fmt::Debug;
 
+use chrono::{DateTime, NaiveDate, Utc};
+
 use crate::{
-    models_mod::user_model::UserModel, traits_mod::create_traits::CreateAsync,
+    models_mod::{
+        alert_model::AlertKind,
+        birthday_model::BirthdayModel,
+        draw_model::DrawModel,
+        engagement_model::EngagementModel,
+        giveaway_model::GiveawayModel,
+        greeting_model::{AiStatsModel, DigestModel, GreetingModel},
+        user_model::UserModel,
+    },
+    traits_mod::create_traits::CreateAsync,
     types_mod::result_types::Res,
 };
 
 #[async_trait::async_trait]
 pub trait DatabaseModule: Send + Sync + CreateAsync + Debug {
-    async fn create_user(&self, user: UserModel) -> Res<()>;
+    /// Creates or updates user, who becomes active again. `today` is saved as the date of
+    /// subscription of new users.
+    async fn create_user(&self, user: UserModel, today: NaiveDate) -> Res<()>;
     async fn get_users(&self) -> Res<Vec<UserModel>>;
+    /// Inactive users, e.g. who blocked the bot, can't win draws until they send `/start` again.
+    async fn set_active(&self, user_id: &str, is_active: bool) -> Res<()>;
+    async fn get_inactive_users(&self) -> Res<Vec<String>>;
+    /// Removes user with all their settings, ratings and giveaway registrations, so they don't
+    /// receive greetings anymore. Referrals are kept, so each user brings one referral at most.
+    async fn delete_user(&self, user_id: &str) -> Res<()>;
+    async fn set_location(&self, user_id: &str, location: &str) -> Res<()>;
+    async fn get_location(&self, user_id: &str) -> Res<Option<String>>;
+    /// Sets language chosen by user, which isn't overwritten by platform's language anymore.
+    async fn set_language(&self, user_id: &str, lang: &str) -> Res<()>;
+    async fn get_language(&self, user_id: &str) -> Res<Option<String>>;
+    async fn set_birthday(&self, user_id: &str, birthday: BirthdayModel) -> Res<()>;
+    async fn is_alert_sent(&self, location: &str, kind: AlertKind, date: NaiveDate) -> Res<bool>;
+    /// Returns `false` if this alert was already sent to the location on this date.
+    async fn mark_alert_sent(&self, location: &str, kind: AlertKind, date: NaiveDate) -> Res<bool>;
+    /// Saves sent greeting and returns its id for rating buttons.
+    async fn save_greeting(&self, greeting: GreetingModel) -> Res<i64>;
+    /// Saves or replaces user's rating of the greeting. Returns `false` if the greeting was sent
+    /// neither to the user nor to the channel.
+    async fn rate_greeting(&self, greeting_id: i64, user_id: &str, is_up: bool) -> Res<bool>;
+    async fn get_ai_stats(&self) -> Res<Vec<AiStatsModel>>;
+    /// Returns number of subscribers and greetings with their ratings since the date.
+    async fn get_digest(&self, since: NaiveDate) -> Res<DigestModel>;
+    async fn save_draw(&self, draw: DrawModel) -> Res<()>;
+    /// Returns last `limit` draws, the newest first.
+    async fn get_draws(&self, limit: u32) -> Res<Vec<DrawModel>>;
+    /// Returns draws made on the date or later, the newest first.
+    async fn get_draws_since(&self, since: NaiveDate) -> Res<Vec<DrawModel>>;
+    /// Opens giveaway registration with the secret seed of its draw and returns its id.
+    async fn open_giveaway(&self, date: NaiveDate, seed: &str) -> Res<i64>;
+    /// Returns the giveaway, which is open for registration, if any.
+    async fn get_open_giveaway(&self) -> Res<Option<GiveawayModel>>;
+    async fn close_giveaway(&self, giveaway_id: i64) -> Res<()>;
+    /// Registers user in the giveaway, returns `false` if they were already registered
+    /// or the giveaway is closed.
+    async fn join_giveaway(&self, giveaway_id: i64, user: &UserModel) -> Res<bool>;
+    async fn get_giveaway_participants(&self, giveaway_id: i64) -> Res<Vec<UserModel>>;
+    /// Saves that new user subscribed by the link of `referrer_id`, returns `false` if user
+    /// isn't new or referrer isn't subscribed. User, who unsubscribed and rejoins, isn't new.
+    async fn add_referral(&self, user_id: &str, referrer_id: &str) -> Res<bool>;
+    async fn get_engagement(&self, user_id: &str) -> Res<EngagementModel>;
+    /// Returns time of the last slot, which the job ran for.
+    async fn get_last_run(&self, job_key: &str) -> Res<Option<DateTime<Utc>>>;
+    /// Marks that the job runs for the slot, returns `false` if it already ran for this or a
+    /// later slot, so the job never runs twice.
+    async fn claim_job_run(&self, job_key: &str, slot: DateTime<Utc>) -> Res<bool>;
 }
@@ -1,62 +1,942 @@
-use sqlx::{Row, SqlitePool, sqlite::SqlitePoolOptions};
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{
+    Row, SqlitePool,
+    sqlite::{SqlitePoolOptions, SqliteRow},
+};
 
 use crate::{
-    db_mod::database::DatabaseModule, models_mod::user_model::UserModel,
-    tools_mod::config_tools::CONFIG, traits_mod::create_traits::CreateAsync,
+    db_mod::database::DatabaseModule,
+    models_mod::{
+        alert_model::AlertKind,
+        birthday_model::BirthdayModel,
+        draw_model::{DrawModel, DrawWinnerModel},
+        engagement_model::EngagementModel,
+        giveaway_model::GiveawayModel,
+        greeting_model::{AiStatsModel, DigestModel, GreetingModel},
+        user_model::UserModel,
+    },
+    tools_mod::config_tools::CONFIG,
+    traits_mod::create_traits::CreateAsync,
     types_mod::result_types::Res,
 };
 
 use std::sync::Arc;
 
+const SCHEMA: [&str; 15] = [
+    "CREATE TABLE IF NOT EXISTS users (
+        id TEXT PRIMARY KEY,
+        username TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS locations (
+        user_id TEXT PRIMARY KEY,
+        location TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS languages (
+        user_id TEXT PRIMARY KEY,
+        lang TEXT NOT NULL,
+        is_explicit INTEGER NOT NULL DEFAULT 0
+    )",
+    "CREATE TABLE IF NOT EXISTS birthdays (
+        user_id TEXT PRIMARY KEY,
+        day INTEGER NOT NULL,
+        month INTEGER NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS sent_alerts (
+        location TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        date TEXT NOT NULL,
+        PRIMARY KEY (location, kind, date)
+    )",
+    "CREATE TABLE IF NOT EXISTS greetings (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id TEXT NOT NULL,
+        text TEXT NOT NULL,
+        model TEXT NOT NULL,
+        prompt_version TEXT NOT NULL,
+        date TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS ratings (
+        greeting_id INTEGER NOT NULL,
+        user_id TEXT NOT NULL,
+        is_up INTEGER NOT NULL,
+        PRIMARY KEY (greeting_id, user_id)
+    )",
+    "CREATE TABLE IF NOT EXISTS draws (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        date TEXT NOT NULL,
+        participants INTEGER NOT NULL,
+        seed TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS inactive_users (
+        user_id TEXT PRIMARY KEY
+    )",
+    "CREATE TABLE IF NOT EXISTS draw_winners (
+        draw_id INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        prize TEXT NOT NULL,
+        user_id TEXT NOT NULL,
+        username TEXT NOT NULL,
+        PRIMARY KEY (draw_id, position)
+    )",
+    "CREATE TABLE IF NOT EXISTS giveaways (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        date TEXT NOT NULL,
+        seed TEXT NOT NULL,
+        is_open INTEGER NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS giveaway_participants (
+        giveaway_id INTEGER NOT NULL,
+        user_id TEXT NOT NULL,
+        username TEXT NOT NULL,
+        PRIMARY KEY (giveaway_id, user_id)
+    )",
+    "CREATE TABLE IF NOT EXISTS subscriptions (
+        user_id TEXT PRIMARY KEY,
+        since TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS referrals (
+        user_id TEXT PRIMARY KEY,
+        referrer_id TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS job_runs (
+        job_key TEXT PRIMARY KEY,
+        slot INTEGER NOT NULL
+    )",
+];
+
+/// Changes of data, which run once after [`SCHEMA`], their count is saved as `user_version`.
+/// New ones are only appended.
+const MIGRATIONS: [&str; 1] = [
+    // Users, who subscribed before subscriptions were tracked, count from the upgrade
+    "INSERT OR IGNORE INTO subscriptions (user_id, since) SELECT id, date('now') FROM users",
+];
+
 #[derive(Clone, Debug)]
 pub struct SqliteDatabase {
     pool: Arc<SqlitePool>,
+    /// Id of the channel, whose greetings anyone can rate
+    channel: String,
 }
 
-#[async_trait::async_trait]
-impl CreateAsync for SqliteDatabase {
+impl SqliteDatabase {
     #[tracing::instrument]
-    async fn new() -> Res<Arc<Self>> {
+    pub async fn connect(url: &str, channel: &str) -> Res<Arc<Self>> {
         let pool = SqlitePoolOptions::new()
             .max_connections(5)
-            .connect(&CONFIG.db_url)
+            .connect(url)
             .await?;
+        for query in SCHEMA {
+            sqlx::query(query).execute(&pool).await?;
+        }
+        Self::migrate(&pool).await?;
         let db = Self {
             pool: Arc::new(pool),
+            channel: channel.into(),
         };
         Ok(Arc::new(db))
     }
+
+    #[tracing::instrument(skip(pool))]
+    async fn migrate(pool: &SqlitePool) -> Res<()> {
+        let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+            .fetch_one(pool)
+            .await?;
+        for (idx, query) in MIGRATIONS
+            .iter()
+            .enumerate()
+            .skip(usize::try_from(version)?)
+        {
+            tracing::info!("Applying database migration {}", idx + 1);
+            let mut tx = pool.begin().await?;
+            sqlx::query(query).execute(&mut *tx).await?;
+            // Pragma doesn't accept bound parameters
+            sqlx::query(&format!("PRAGMA user_version = {}", idx + 1))
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+        }
+        Ok(())
+    }
+
+    /// Loads winners of the draws in order of their positions.
+    #[tracing::instrument(skip(rows))]
+    async fn with_winners(&self, rows: Vec<SqliteRow>) -> Res<Vec<DrawModel>> {
+        let mut draws = Vec::with_capacity(rows.len());
+        for row in rows {
+            let winners = sqlx::query(
+                "SELECT prize, user_id, username
+                    FROM draw_winners
+                    WHERE draw_id = ?1
+                    ORDER BY position",
+            )
+            .bind(row.get::<i64, _>("id"))
+            .fetch_all(&*self.pool)
+            .await?
+            .into_iter()
+            .map(|winner| DrawWinnerModel {
+                prize: winner.get::<String, _>("prize"),
+                user_id: winner.get::<String, _>("user_id"),
+                username: winner.get::<String, _>("username"),
+            })
+            .collect();
+
+            draws.push(DrawModel {
+                date: row.get::<String, _>("date").parse()?,
+                winners,
+                participants: row.get::<u32, _>("participants"),
+                seed: row.get::<String, _>("seed"),
+            });
+        }
+        Ok(draws)
+    }
+}
+
+#[async_trait::async_trait]
+impl CreateAsync for SqliteDatabase {
+    #[tracing::instrument]
+    async fn new() -> Res<Arc<Self>> {
+        Self::connect(&CONFIG.db_url, &CONFIG.channel).await
+    }
 }
 
 #[async_trait::async_trait]
 impl DatabaseModule for SqliteDatabase {
     #[tracing::instrument]
-    async fn create_user(&self, user: UserModel) -> Res<()> {
+    async fn create_user(&self, user: UserModel, today: NaiveDate) -> Res<()> {
         sqlx::query(
             "INSERT INTO users (id, username)
                 VALUES (?1, ?2)
                 ON CONFLICT(id) DO UPDATE SET
                     username = excluded.username",
         )
-        .bind(user.id)
+        .bind(&user.id)
         .bind(user.username)
         .execute(&*self.pool)
         .await?;
+        self.set_active(&user.id, true).await?;
+
+        sqlx::query("INSERT OR IGNORE INTO subscriptions (user_id, since) VALUES (?1, ?2)")
+            .bind(&user.id)
+            .bind(today.to_string())
+            .execute(&*self.pool)
+            .await?;
+
+        if let Some(lang) = user.lang {
+            sqlx::query(
+                "INSERT INTO languages (user_id, lang)
+                    VALUES (?1, ?2)
+                    ON CONFLICT(user_id) DO UPDATE SET
+                        lang = excluded.lang
+                    WHERE is_explicit = 0",
+            )
+            .bind(user.id)
+            .bind(lang)
+            .execute(&*self.pool)
+            .await?;
+        }
         Ok(())
     }
 
     #[tracing::instrument]
     async fn get_users(&self) -> Res<Vec<UserModel>> {
-        let rows = sqlx::query("SELECT id, username FROM users")
+        let rows = sqlx::query(
+            "SELECT users.id, users.username, locations.location, languages.lang,
+                    birthdays.day, birthdays.month
+                FROM users
+                LEFT JOIN locations ON locations.user_id = users.id
+                LEFT JOIN languages ON languages.user_id = users.id
+                LEFT JOIN birthdays ON birthdays.user_id = users.id",
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+        let users = rows
+            .into_iter()
+            .map(|row| UserModel {
+                id: row.get::<String, _>("id"),
+                username: row.get::<String, _>("username"),
+                location: row.get::<Option<String>, _>("location"),
+                lang: row.get::<Option<String>, _>("lang"),
+                birthday: row
+                    .get::<Option<u32>, _>("day")
+                    .zip(row.get::<Option<u32>, _>("month"))
+                    .map(|(day, month)| BirthdayModel { day, month }),
+            })
+            .collect();
+        Ok(users)
+    }
+
+    #[tracing::instrument]
+    async fn set_active(&self, user_id: &str, is_active: bool) -> Res<()> {
+        let query = if is_active {
+            "DELETE FROM inactive_users WHERE user_id = ?1"
+        } else {
+            "INSERT OR IGNORE INTO inactive_users (user_id) VALUES (?1)"
+        };
+        sqlx::query(query)
+            .bind(user_id)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    async fn get_inactive_users(&self) -> Res<Vec<String>> {
+        let rows = sqlx::query("SELECT user_id FROM inactive_users")
             .fetch_all(&*self.pool)
             .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| row.get::<String, _>("user_id"))
+            .collect())
+    }
+
+    #[tracing::instrument]
+    async fn delete_user(&self, user_id: &str) -> Res<()> {
+        // Referrals are kept, so the user doesn't bring their referrer a new ticket by rejoining.
+        // Greetings and draws are kept too, they are history of AI stats and of verifiable draws
+        let mut tx = self.pool.begin().await?;
+        for query in [
+            "DELETE FROM users WHERE id = ?1",
+            "DELETE FROM locations WHERE user_id = ?1",
+            "DELETE FROM languages WHERE user_id = ?1",
+            "DELETE FROM birthdays WHERE user_id = ?1",
+            "DELETE FROM inactive_users WHERE user_id = ?1",
+            "DELETE FROM subscriptions WHERE user_id = ?1",
+            "DELETE FROM ratings WHERE user_id = ?1",
+            "DELETE FROM giveaway_participants WHERE user_id = ?1",
+        ] {
+            sqlx::query(query).bind(user_id).execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    async fn set_location(&self, user_id: &str, location: &str) -> Res<()> {
+        sqlx::query(
+            "INSERT INTO locations (user_id, location)
+                VALUES (?1, ?2)
+                ON CONFLICT(user_id) DO UPDATE SET
+                    location = excluded.location",
+        )
+        .bind(user_id)
+        .bind(location)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    async fn get_location(&self, user_id: &str) -> Res<Option<String>> {
+        let row = sqlx::query("SELECT location FROM locations WHERE user_id = ?1")
+            .bind(user_id)
+            .fetch_optional(&*self.pool)
+            .await?;
+        Ok(row.map(|row| row.get::<String, _>("location")))
+    }
+
+    #[tracing::instrument]
+    async fn set_language(&self, user_id: &str, lang: &str) -> Res<()> {
+        sqlx::query(
+            "INSERT INTO languages (user_id, lang, is_explicit)
+                VALUES (?1, ?2, 1)
+                ON CONFLICT(user_id) DO UPDATE SET
+                    lang = excluded.lang,
+                    is_explicit = 1",
+        )
+        .bind(user_id)
+        .bind(lang)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    async fn get_language(&self, user_id: &str) -> Res<Option<String>> {
+        let row = sqlx::query("SELECT lang FROM languages WHERE user_id = ?1")
+            .bind(user_id)
+            .fetch_optional(&*self.pool)
+            .await?;
+        Ok(row.map(|row| row.get::<String, _>("lang")))
+    }
+
+    #[tracing::instrument]
+    async fn set_birthday(&self, user_id: &str, birthday: BirthdayModel) -> Res<()> {
+        sqlx::query(
+            "INSERT INTO birthdays (user_id, day, month)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT(user_id) DO UPDATE SET
+                    day = excluded.day,
+                    month = excluded.month",
+        )
+        .bind(user_id)
+        .bind(birthday.day)
+        .bind(birthday.month)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    async fn is_alert_sent(&self, location: &str, kind: AlertKind, date: NaiveDate) -> Res<bool> {
+        let row: Option<(i64,)> = sqlx::query_as(
+            "SELECT 1 FROM sent_alerts WHERE location = ?1 AND kind = ?2 AND date = ?3",
+        )
+        .bind(location)
+        .bind(kind.key())
+        .bind(date.to_string())
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.is_some())
+    }
+
+    #[tracing::instrument]
+    async fn mark_alert_sent(&self, location: &str, kind: AlertKind, date: NaiveDate) -> Res<bool> {
+        let res = sqlx::query(
+            "INSERT OR IGNORE INTO sent_alerts (location, kind, date)
+                VALUES (?1, ?2, ?3)",
+        )
+        .bind(location)
+        .bind(kind.key())
+        .bind(date.to_string())
+        .execute(&*self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    #[tracing::instrument]
+    async fn save_greeting(&self, greeting: GreetingModel) -> Res<i64> {
+        let res = sqlx::query(
+            "INSERT INTO greetings (user_id, text, model, prompt_version, date)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(greeting.user_id)
+        .bind(greeting.text)
+        .bind(greeting.model)
+        .bind(greeting.prompt_version)
+        .bind(greeting.date.to_string())
+        .execute(&*self.pool)
+        .await?;
+        Ok(res.last_insert_rowid())
+    }
+
+    #[tracing::instrument]
+    async fn rate_greeting(&self, greeting_id: i64, user_id: &str, is_up: bool) -> Res<bool> {
+        // Ratings give draw tickets, so ids of other users' greetings from forged buttons are ignored
+        let res = sqlx::query(
+            "INSERT INTO ratings (greeting_id, user_id, is_up)
+                SELECT ?1, ?2, ?3
+                FROM greetings
+                WHERE greetings.id = ?1 AND greetings.user_id IN (?2, ?4)
+                ON CONFLICT(greeting_id, user_id) DO UPDATE SET
+                    is_up = excluded.is_up",
+        )
+        .bind(greeting_id)
+        .bind(user_id)
+        .bind(is_up)
+        .bind(&self.channel)
+        .execute(&*self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    #[tracing::instrument]
+    async fn get_ai_stats(&self) -> Res<Vec<AiStatsModel>> {
+        let rows = sqlx::query(
+            "SELECT greetings.model, greetings.prompt_version,
+                    COUNT(DISTINCT greetings.id) AS greetings,
+                    COALESCE(SUM(ratings.is_up = 1), 0) AS ups,
+                    COALESCE(SUM(ratings.is_up = 0), 0) AS downs
+                FROM greetings
+                LEFT JOIN ratings ON ratings.greeting_id = greetings.id
+                GROUP BY greetings.model, greetings.prompt_version
+                ORDER BY greetings.model, greetings.prompt_version",
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+        let stats = rows
+            .into_iter()
+            .map(|row| AiStatsModel {
+                model: row.get::<String, _>("model"),
+                prompt_version: row.get::<String, _>("prompt_version"),
+                greetings: row.get::<i64, _>("greetings"),
+                ups: row.get::<i64, _>("ups"),
+                downs: row.get::<i64, _>("downs"),
+            })
+            .collect();
+        Ok(stats)
+    }
+
+    #[tracing::instrument]
+    async fn get_digest(&self, since: NaiveDate) -> Res<DigestModel> {
+        let row = sqlx::query(
+            "SELECT
+                (SELECT COUNT(*) FROM users) AS subscribers,
+                (SELECT COUNT(*) FROM greetings WHERE date >= ?1) AS greetings,
+                COALESCE(SUM(ratings.is_up = 1), 0) AS ups,
+                COALESCE(SUM(ratings.is_up = 0), 0) AS downs
+                FROM ratings
+                JOIN greetings ON greetings.id = ratings.greeting_id
+                WHERE greetings.date >= ?1",
+        )
+        .bind(since.to_string())
+        .fetch_one(&*self.pool)
+        .await?;
+        Ok(DigestModel {
+            subscribers: row.get::<i64, _>("subscribers"),
+            greetings: row.get::<i64, _>("greetings"),
+            ups: row.get::<i64, _>("ups"),
+            downs: row.get::<i64, _>("downs"),
+        })
+    }
+
+    #[tracing::instrument]
+    async fn save_draw(&self, draw: DrawModel) -> Res<()> {
+        let mut tx = self.pool.begin().await?;
+        let draw_id =
+            sqlx::query("INSERT INTO draws (date, participants, seed) VALUES (?1, ?2, ?3)")
+                .bind(draw.date.to_string())
+                .bind(draw.participants)
+                .bind(draw.seed)
+                .execute(&mut *tx)
+                .await?
+                .last_insert_rowid();
+
+        for (position, winner) in draw.winners.into_iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO draw_winners (draw_id, position, prize, user_id, username)
+                    VALUES (?1, ?2, ?3, ?4, ?5)",
+            )
+            .bind(draw_id)
+            .bind(i64::try_from(position)?)
+            .bind(winner.prize)
+            .bind(winner.user_id)
+            .bind(winner.username)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    async fn get_draws(&self, limit: u32) -> Res<Vec<DrawModel>> {
+        let rows = sqlx::query(
+            "SELECT id, date, participants, seed
+                FROM draws
+                ORDER BY id DESC
+                LIMIT ?1",
+        )
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await?;
+        self.with_winners(rows).await
+    }
+
+    #[tracing::instrument]
+    async fn get_draws_since(&self, since: NaiveDate) -> Res<Vec<DrawModel>> {
+        let rows = sqlx::query(
+            "SELECT id, date, participants, seed
+                FROM draws
+                WHERE date >= ?1
+                ORDER BY id DESC",
+        )
+        .bind(since.to_string())
+        .fetch_all(&*self.pool)
+        .await?;
+        self.with_winners(rows).await
+    }
+
+    #[tracing::instrument]
+    async fn open_giveaway(&self, date: NaiveDate, seed: &str) -> Res<i64> {
+        let res = sqlx::query("INSERT INTO giveaways (date, seed, is_open) VALUES (?1, ?2, 1)")
+            .bind(date.to_string())
+            .bind(seed)
+            .execute(&*self.pool)
+            .await?;
+        Ok(res.last_insert_rowid())
+    }
+
+    #[tracing::instrument]
+    async fn get_open_giveaway(&self) -> Res<Option<GiveawayModel>> {
+        let row = sqlx::query("SELECT id, seed FROM giveaways WHERE is_open = 1 ORDER BY id DESC")
+            .fetch_optional(&*self.pool)
+            .await?;
+        Ok(row.map(|row| GiveawayModel {
+            id: row.get::<i64, _>("id"),
+            seed: row.get::<String, _>("seed"),
+        }))
+    }
+
+    #[tracing::instrument]
+    async fn close_giveaway(&self, giveaway_id: i64) -> Res<()> {
+        sqlx::query("UPDATE giveaways SET is_open = 0 WHERE id = ?1")
+            .bind(giveaway_id)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    async fn join_giveaway(&self, giveaway_id: i64, user: &UserModel) -> Res<bool> {
+        let res = sqlx::query(
+            "INSERT OR IGNORE INTO giveaway_participants (giveaway_id, user_id, username)
+                SELECT ?1, ?2, ?3
+                WHERE EXISTS (SELECT 1 FROM giveaways WHERE id = ?1 AND is_open = 1)",
+        )
+        .bind(giveaway_id)
+        .bind(&user.id)
+        .bind(&user.username)
+        .execute(&*self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    #[tracing::instrument]
+    async fn get_giveaway_participants(&self, giveaway_id: i64) -> Res<Vec<UserModel>> {
+        // Participants may join from the channel without `/start`, so users table is optional
+        let rows = sqlx::query(
+            "SELECT giveaway_participants.user_id, giveaway_participants.username,
+                    languages.lang
+                FROM giveaway_participants
+                LEFT JOIN languages ON languages.user_id = giveaway_participants.user_id
+                WHERE giveaway_participants.giveaway_id = ?1",
+        )
+        .bind(giveaway_id)
+        .fetch_all(&*self.pool)
+        .await?;
         let users = rows
             .into_iter()
             .map(|row| UserModel {
-                id: row.get::<String, _>("id"),
+                id: row.get::<String, _>("user_id"),
                 username: row.get::<String, _>("username"),
+                location: None,
+                lang: row.get::<Option<String>, _>("lang"),
+                birthday: None,
             })
             .collect();
         Ok(users)
     }
+
+    #[tracing::instrument]
+    async fn add_referral(&self, user_id: &str, referrer_id: &str) -> Res<bool> {
+        let res = sqlx::query(
+            "INSERT OR IGNORE INTO referrals (user_id, referrer_id)
+                SELECT ?1, ?2
+                WHERE ?1 != ?2
+                    AND NOT EXISTS (SELECT 1 FROM users WHERE id = ?1)
+                    AND EXISTS (SELECT 1 FROM users WHERE id = ?2)",
+        )
+        .bind(user_id)
+        .bind(referrer_id)
+        .execute(&*self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    #[tracing::instrument]
+    async fn get_engagement(&self, user_id: &str) -> Res<EngagementModel> {
+        let row = sqlx::query(
+            "SELECT
+                (SELECT since FROM subscriptions WHERE user_id = ?1) AS since,
+                (SELECT COUNT(*) FROM ratings
+                    JOIN greetings ON greetings.id = ratings.greeting_id
+                    WHERE ratings.user_id = ?1 AND greetings.user_id IN (?1, ?2)) AS ratings,
+                (SELECT COUNT(*) FROM referrals WHERE referrer_id = ?1) AS referrals",
+        )
+        .bind(user_id)
+        .bind(&self.channel)
+        .fetch_one(&*self.pool)
+        .await?;
+        Ok(EngagementModel {
+            since: row
+                .get::<Option<String>, _>("since")
+                .map(|since| since.parse())
+                .transpose()?,
+            ratings: row.get::<u32, _>("ratings"),
+            referrals: row.get::<u32, _>("referrals"),
+        })
+    }
+
+    #[tracing::instrument]
+    async fn get_last_run(&self, job_key: &str) -> Res<Option<DateTime<Utc>>> {
+        let row = sqlx::query("SELECT slot FROM job_runs WHERE job_key = ?1")
+            .bind(job_key)
+            .fetch_optional(&*self.pool)
+            .await?;
+        Ok(row.and_then(|row| DateTime::from_timestamp(row.get::<i64, _>("slot"), 0)))
+    }
+
+    #[tracing::instrument]
+    async fn claim_job_run(&self, job_key: &str, slot: DateTime<Utc>) -> Res<bool> {
+        // Single statement, so concurrent claims of one slot can't both succeed
+        let res = sqlx::query(
+            "INSERT INTO job_runs (job_key, slot) VALUES (?1, ?2)
+                ON CONFLICT (job_key) DO UPDATE SET slot = excluded.slot
+                WHERE excluded.slot > job_runs.slot",
+        )
+        .bind(job_key)
+        .bind(slot.timestamp())
+        .execute(&*self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod sqlite_tests {
+    use std::{env, fs, sync::Arc};
+
+    use chrono::NaiveDate;
+
+    use crate::{
+        db_mod::{database::DatabaseModule, sqlite_database::SqliteDatabase},
+        models_mod::{
+            draw_model::{DrawModel, DrawWinnerModel},
+            greeting_model::GreetingModel,
+            user_model::UserModel,
+        },
+    };
+
+    const CHANNEL: &str = "100";
+
+    /// Url of a fresh temp file, which is unique for the test.
+    fn test_url(name: &str) -> String {
+        let path = env::temp_dir().join(format!("greeting_{name}_{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        format!("sqlite://{}?mode=rwc", path.display())
+    }
+
+    async fn connect(url: &str) -> Arc<SqliteDatabase> {
+        SqliteDatabase::connect(url, CHANNEL)
+            .await
+            .expect("Failed to create database")
+    }
+
+    async fn test_db(name: &str) -> Arc<SqliteDatabase> {
+        connect(&test_url(name)).await
+    }
+
+    async fn greet(db: &SqliteDatabase, user_id: &str) -> i64 {
+        db.save_greeting(GreetingModel {
+            user_id: user_id.into(),
+            text: "Hi".into(),
+            model: "model".into(),
+            prompt_version: "v1".into(),
+            date: NaiveDate::from_ymd_opt(2026, 10, 19).expect("Invalid date"),
+        })
+        .await
+        .expect("Failed to save greeting")
+    }
+
+    #[tokio::test]
+    async fn test_rate_only_own_greetings() {
+        let db = test_db("rate").await;
+        let own = greet(&db, "2").await;
+        let channel = greet(&db, CHANNEL).await;
+        let other = greet(&db, "3").await;
+
+        assert!(
+            db.rate_greeting(own, "2", true)
+                .await
+                .expect("Failed to rate")
+        );
+        assert!(
+            db.rate_greeting(channel, "2", false)
+                .await
+                .expect("Failed to rate")
+        );
+        // Forged buttons with ids of other users' greetings
+        assert!(
+            !db.rate_greeting(other, "2", true)
+                .await
+                .expect("Failed to rate")
+        );
+        assert!(
+            !db.rate_greeting(other + 1, "2", true)
+                .await
+                .expect("Failed to rate")
+        );
+
+        let engagement = db
+            .get_engagement("2")
+            .await
+            .expect("Failed to get engagement");
+        assert_eq!(engagement.ratings, 2);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_subscriptions() {
+        let url = test_url("backfill");
+        let db = connect(&url).await;
+        // Database, which was created before subscriptions were tracked
+        for query in [
+            "DELETE FROM subscriptions",
+            "PRAGMA user_version = 0",
+            "INSERT INTO users (id, username) VALUES ('2', 'Bob')",
+        ] {
+            sqlx::query(query)
+                .execute(&*db.pool)
+                .await
+                .expect("Failed to prepare database");
+        }
+
+        let db = connect(&url).await;
+        let count = |db: Arc<SqliteDatabase>| async move {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM subscriptions")
+                .fetch_one(&*db.pool)
+                .await
+                .expect("Failed to count subscriptions")
+        };
+        assert_eq!(count(db.clone()).await, 1);
+
+        // Backfill runs once, so it doesn't subscribe anyone on the next start
+        sqlx::query("INSERT INTO users (id, username) VALUES ('3', 'Carol')")
+            .execute(&*db.pool)
+            .await
+            .expect("Failed to insert user");
+        let db = connect(&url).await;
+        assert_eq!(count(db).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_join_only_open_giveaway() {
+        let db = test_db("join").await;
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).expect("Invalid date");
+        let giveaway = db
+            .open_giveaway(date, "seed")
+            .await
+            .expect("Failed to open giveaway");
+        let user = |id: &str| UserModel {
+            id: id.into(),
+            username: format!("user{id}"),
+            location: None,
+            lang: None,
+            birthday: None,
+        };
+
+        assert!(
+            db.join_giveaway(giveaway, &user("2"))
+                .await
+                .expect("Failed to join")
+        );
+        assert!(
+            !db.join_giveaway(giveaway, &user("2"))
+                .await
+                .expect("Failed to join")
+        );
+        db.close_giveaway(giveaway)
+            .await
+            .expect("Failed to close giveaway");
+        assert!(
+            !db.join_giveaway(giveaway, &user("3"))
+                .await
+                .expect("Failed to join")
+        );
+        assert!(
+            !db.join_giveaway(giveaway + 1, &user("3"))
+                .await
+                .expect("Failed to join")
+        );
+
+        let participants = db
+            .get_giveaway_participants(giveaway)
+            .await
+            .expect("Failed to get participants");
+        assert_eq!(participants.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_draws_since() {
+        let db = test_db("draws_since").await;
+        for (day, winner) in [(1, "old"), (10, "first"), (19, "second")] {
+            db.save_draw(DrawModel {
+                date: NaiveDate::from_ymd_opt(2026, 10, day).expect("Invalid date"),
+                winners: vec![DrawWinnerModel {
+                    prize: String::new(),
+                    user_id: winner.into(),
+                    username: winner.into(),
+                }],
+                participants: 1,
+                seed: "42".into(),
+            })
+            .await
+            .expect("Failed to save draw");
+        }
+
+        let since = NaiveDate::from_ymd_opt(2026, 10, 10).expect("Invalid date");
+        let winners = db
+            .get_draws_since(since)
+            .await
+            .expect("Failed to get draws")
+            .into_iter()
+            .flat_map(|draw| draw.winners)
+            .map(|winner| winner.user_id)
+            .collect::<Vec<_>>();
+        assert_eq!(winners, ["second", "first"]);
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_keeps_referral() {
+        let db = test_db("delete").await;
+        let today = NaiveDate::from_ymd_opt(2026, 10, 19).expect("Invalid date");
+        let user = |id: &str| UserModel {
+            id: id.into(),
+            username: format!("user{id}"),
+            location: None,
+            lang: None,
+            birthday: None,
+        };
+        db.create_user(user("1"), today)
+            .await
+            .expect("Failed to create user");
+        assert!(
+            db.add_referral("2", "1")
+                .await
+                .expect("Failed to add referral")
+        );
+        db.create_user(user("2"), today)
+            .await
+            .expect("Failed to create user");
+        let greeting = greet(&db, "2").await;
+        db.rate_greeting(greeting, "2", true)
+            .await
+            .expect("Failed to rate");
+        let giveaway = db
+            .open_giveaway(today, "seed")
+            .await
+            .expect("Failed to open giveaway");
+        db.join_giveaway(giveaway, &user("2"))
+            .await
+            .expect("Failed to join");
+
+        db.delete_user("2").await.expect("Failed to delete user");
+        assert_eq!(
+            db.get_engagement("2")
+                .await
+                .expect("Failed to get engagement")
+                .ratings,
+            0
+        );
+        assert!(
+            db.get_giveaway_participants(giveaway)
+                .await
+                .expect("Failed to get participants")
+                .is_empty()
+        );
+
+        // Rejoining by the referral link doesn't credit the referrer again
+        assert!(
+            !db.add_referral("2", "1")
+                .await
+                .expect("Failed to add referral")
+        );
+        assert_eq!(
+            db.get_engagement("1")
+                .await
+                .expect("Failed to get engagement")
+                .referrals,
+            1
+        );
+    }
 }
@@ -0,0 +1,101 @@
+use minijinja::context;
+
+use crate::{
+    DB, PLATFORM,
+    handlers_mod::lang_handler::user_lang,
+    models_mod::user_model::UserModel,
+    tools_mod::{config_tools::CONFIG, i18n_tools::tr, template_tools::render},
+};
+
+const DEFAULT_AI_REPORT_FMT: &str = "Оценки приветствий:\
+    {% for row in stats %}\n<code>{{ row.model }}</code>, промпт <code>{{ row.prompt_version }}</code>: \
+    {{ row.greetings }} шт., 👍 {{ row.ups }} 👎 {{ row.downs }}\
+    {% if row.average is not none %}, {{ row.average }}% 👍{% endif %}\
+    {% else %}\nпока нет данных{% endfor %}";
+
+/// Shows admin average ratings of greetings per AI model and prompt version.
+#[tracing::instrument]
+pub async fn handle_ai_report(user: UserModel) {
+    let platform = PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone();
+    let lang = user_lang(&user).await;
+
+    if user.id != CONFIG.admin {
+        platform
+            .send_message(
+                user,
+                &tr(&lang, "admin_only", "Команда доступна только админу"),
+            )
+            .await
+            .expect("Failed to send message");
+        return;
+    }
+
+    let stats = DB
+        .get()
+        .expect("Failed to get DB instance")
+        .get_ai_stats()
+        .await
+        .expect("Error accessing to database")
+        .into_iter()
+        .map(|row| {
+            context! {
+                model => row.model,
+                prompt_version => row.prompt_version,
+                greetings => row.greetings,
+                ups => row.ups,
+                downs => row.downs,
+                average => row.average().map(|avg| format!("{:.0}", avg * 100.0)),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    platform
+        .send_message(
+            user,
+            &render(
+                &tr(&lang, "ai_report_fmt", DEFAULT_AI_REPORT_FMT),
+                context! { stats },
+            ),
+        )
+        .await
+        .expect("Failed to send message");
+}
+
+#[cfg(test)]
+mod ai_report_tests {
+    use minijinja::context;
+
+    use crate::{
+        handlers_mod::ai_report_handler::DEFAULT_AI_REPORT_FMT,
+        tools_mod::template_tools::{check_template, render},
+    };
+
+    #[test]
+    fn test_default_ai_report_fmt() {
+        assert!(check_template(DEFAULT_AI_REPORT_FMT, &["stats"]).is_ok());
+
+        let stats = vec![context! {
+            model => "qwen3:30b",
+            prompt_version => "ba7816bf",
+            greetings => 4,
+            ups => 3,
+            downs => 1,
+            average => "75",
+        }];
+        assert_eq!(
+            render(DEFAULT_AI_REPORT_FMT, context! { stats }),
+            "Оценки приветствий:\n<code>qwen3:30b</code>, промпт <code>ba7816bf</code>: \
+             4 шт., 👍 3 👎 1, 75% 👍"
+        );
+        assert_eq!(
+            render(
+                DEFAULT_AI_REPORT_FMT,
+                context! { stats => Vec::<i32>::new() }
+            ),
+            "Оценки приветствий:\nпока нет данных"
+        );
+    }
+}
@@ -1,7 +1,29 @@
+use std::sync::Arc;
+
 use crate::{
-    PLATFORM, handlers_mod::start_handler::handle_start, models_mod::user_model::UserModel,
+    CLOCK, PLATFORM,
+    clock_mod::clock::Clock,
+    handlers_mod::{
+        ai_report_handler::handle_ai_report,
+        birthday_handler::handle_birthday,
+        city_handler::handle_city,
+        draw_handler::handle_draws,
+        giveaway_handler::{handle_giveaway, handle_join},
+        lang_handler::handle_lang,
+        mychances_handler::handle_mychances,
+        rate_handler::handle_rate,
+        start_handler::handle_start,
+        unsubscribe_handler::handle_unsubscribe,
+        weather_handler::handle_weather,
+    },
+    models_mod::user_model::UserModel,
 };
 
+// Handlers can't capture, so the clock is read on each call and passed down from here
+fn clock() -> Arc<dyn Clock> {
+    CLOCK.get().expect("Failed to get clock instance").clone()
+}
+
 #[tracing::instrument]
 pub async fn bind_all_commands() {
     let platform = PLATFORM
@@ -11,6 +33,107 @@ pub async fn bind_all_commands() {
 
     platform
         .clone()
-        .bind("/start", |user: UserModel| Box::pin(handle_start(user)))
+        .bind("/start", |user: UserModel, args: String| {
+            Box::pin(handle_start(user, args, clock()))
+        })
+        .await;
+
+    platform
+        .clone()
+        .bind("/city", |user: UserModel, args: String| {
+            Box::pin(handle_city(user, args))
+        })
+        .await;
+
+    platform
+        .clone()
+        .bind("/weather", |user: UserModel, args: String| {
+            Box::pin(handle_weather(user, args))
+        })
+        .await;
+
+    platform
+        .clone()
+        .bind("/lang", |user: UserModel, args: String| {
+            Box::pin(handle_lang(user, args))
+        })
+        .await;
+
+    platform
+        .clone()
+        .bind("/birthday", |user: UserModel, args: String| {
+            Box::pin(handle_birthday(user, args))
+        })
+        .await;
+
+    platform
+        .clone()
+        .bind("/unsubscribe", |user: UserModel, _args: String| {
+            Box::pin(handle_unsubscribe(user))
+        })
+        .await;
+
+    platform
+        .clone()
+        .bind("/ai_report", |user: UserModel, _args: String| {
+            Box::pin(handle_ai_report(user))
+        })
+        .await;
+
+    platform
+        .clone()
+        .bind("/draws", |user: UserModel, _args: String| {
+            Box::pin(handle_draws(user))
+        })
+        .await;
+
+    platform
+        .clone()
+        .bind("/giveaway", |user: UserModel, _args: String| {
+            Box::pin(handle_giveaway(user, clock()))
+        })
+        .await;
+
+    platform
+        .clone()
+        .bind("/join", |user: UserModel, _args: String| {
+            Box::pin(handle_join(user))
+        })
+        .await;
+
+    platform
+        .clone()
+        .bind("/mychances", |user: UserModel, _args: String| {
+            Box::pin(handle_mychances(user, clock()))
+        })
+        .await;
+
+    platform
+        .clone()
+        .bind_callback("rate", |user: UserModel, args: String| {
+            Box::pin(handle_rate(user, args))
+        })
+        .await;
+
+    // Without args it asks the user to send `/city <city>`
+    platform
+        .clone()
+        .bind_callback("city", |user: UserModel, args: String| {
+            Box::pin(handle_city(user, args))
+        })
+        .await;
+
+    platform
+        .clone()
+        .bind_callback("unsubscribe", |user: UserModel, _args: String| {
+            Box::pin(handle_unsubscribe(user))
+        })
+        .await;
+
+    platform
+        .clone()
+        .bind_callback("join", |user: UserModel, _args: String| {
+            Box::pin(handle_join(user))
+        })
         .await;
 }
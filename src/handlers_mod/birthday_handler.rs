@@ -0,0 +1,52 @@
+use crate::{
+    DB, PLATFORM,
+    handlers_mod::lang_handler::user_lang,
+    models_mod::{birthday_model::BirthdayModel, user_model::UserModel},
+    tools_mod::{i18n_tools::tr, template_tools::render},
+};
+use minijinja::context;
+
+#[tracing::instrument]
+pub async fn handle_birthday(user: UserModel, date: String) {
+    let platform = PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone();
+    let lang = user_lang(&user).await;
+
+    let Some(birthday) = BirthdayModel::parse(&date) else {
+        platform
+            .send_message(
+                user,
+                &tr(
+                    &lang,
+                    "birthday_usage",
+                    "Укажите день рождения в формате ДД.ММ, например: /birthday 31.12",
+                ),
+            )
+            .await
+            .expect("Failed to send message");
+        return;
+    };
+
+    DB.get()
+        .expect("Failed to get DB instance")
+        .set_birthday(&user.id, birthday)
+        .await
+        .expect("Error accessing to database");
+
+    platform
+        .send_message(
+            user,
+            &render(
+                &tr(
+                    &lang,
+                    "birthday_saved_fmt",
+                    "День рождения сохранён: {{ birthday }}",
+                ),
+                context! { birthday => birthday.to_string() },
+            ),
+        )
+        .await
+        .expect("Failed to send message");
+}
@@ -0,0 +1,47 @@
+use crate::{
+    DB, PLATFORM,
+    handlers_mod::lang_handler::user_lang,
+    models_mod::user_model::UserModel,
+    tools_mod::{config_tools::CONFIG, i18n_tools::tr, template_tools::render},
+};
+use minijinja::context;
+
+#[tracing::instrument]
+pub async fn handle_city(user: UserModel, city: String) {
+    let platform = PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone();
+    let lang = user_lang(&user).await;
+
+    let user_id = user.id.clone();
+
+    if city.is_empty() {
+        if let Err(err) = platform
+            .notify(
+                user,
+                &tr(&lang, "city_usage", "Укажите город, например: /city Москва"),
+            )
+            .await
+        {
+            tracing::warn!("Failed to reply to {user_id} on city: {err}");
+        }
+        return;
+    }
+
+    DB.get()
+        .expect("Failed to get DB instance")
+        .set_location(&user.id, &city)
+        .await
+        .expect("Error accessing to database");
+
+    if let Err(err) = platform
+        .notify(
+            user,
+            &render(&tr(&lang, "city_fmt", &CONFIG.city_fmt), context! { city }),
+        )
+        .await
+    {
+        tracing::warn!("Failed to reply to {user_id} on city: {err}");
+    }
+}
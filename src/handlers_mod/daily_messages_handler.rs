@@ -1,79 +1,505 @@
+use std::collections::HashMap;
+
 use crate::{
-    AI, DB, PLATFORM, WEATHER,
-    handlers_mod::{date_handler::format_datetime_russian, formats_handler::weather_to_emoji},
-    models_mod::user_model::UserModel,
-    tools_mod::config_tools::CONFIG,
+    AI, CALENDAR, DB, PLATFORM, WEATHER,
+    clock_mod::clock::Clock,
+    handlers_mod::{date_handler::date_formatter_for, formats_handler::format_weather},
+    models_mod::{
+        button_model::ButtonModel, greeting_model::GreetingModel, media_model::MediaModel,
+        user_model::UserModel,
+    },
+    tools_mod::{
+        config_tools::CONFIG,
+        hash_tools::prompt_version,
+        i18n_tools::{I18N, tr},
+        template_tools::{render, render_text, safe},
+    },
 };
-use chrono::Utc;
-use string_format::string_format;
+use chrono::{DateTime, NaiveDate, Utc};
+use minijinja::{Value, context};
 
+/// Buttons under the greeting. Rating is shown only for saved AI responses,
+/// channel gets only rating, because its settings can't be changed.
 #[tracing::instrument]
-async fn process_user(user: UserModel, weather: String) {
-    let response = AI
-        .get()
-        .expect("Failed to get AI instance")
-        .clone()
-        .process(weather.clone())
-        .await
-        .unwrap_or_else(|_| CONFIG.ai_msg_off.clone());
+fn greeting_buttons(
+    lang: &str,
+    greeting_id: Option<i64>,
+    is_channel: bool,
+) -> Vec<Vec<ButtonModel>> {
+    let mut buttons = vec![];
+    if let Some(id) = greeting_id {
+        buttons.push(vec![
+            ButtonModel::new(
+                tr(lang, "button_rate_up", "👍"),
+                "rate",
+                &format!("{id}:up"),
+            ),
+            ButtonModel::new(
+                tr(lang, "button_rate_down", "👎"),
+                "rate",
+                &format!("{id}:down"),
+            ),
+        ]);
+    }
+    if !is_channel {
+        buttons.push(vec![ButtonModel::new(
+            tr(lang, "button_city", "🏙️ Сменить город"),
+            "city",
+            "",
+        )]);
+        buttons.push(vec![ButtonModel::new(
+            tr(lang, "button_unsubscribe", "🔕 Отписаться"),
+            "unsubscribe",
+            "",
+        )]);
+    }
+    buttons
+}
+
+#[tracing::instrument]
+async fn process_user(
+    user: UserModel,
+    lang: String,
+    weather: String,
+    prompt: String,
+    version: String,
+    media: Option<MediaModel>,
+    now: DateTime<Utc>,
+) {
+    let formatter = date_formatter_for(&lang);
+    let today = formatter.local_date(now);
+    let is_birthday = user.birthday.is_some_and(|birthday| birthday.is_on(today));
 
-    let now = Utc::now();
+    let birthday_fmt = tr(&lang, "birthday_fmt", &CONFIG.birthday_fmt);
+    let greeting_fmt = if is_birthday && !birthday_fmt.is_empty() {
+        birthday_fmt
+    } else {
+        tr(&lang, "greeting_fmt", &CONFIG.greeting_fmt)
+    };
+    let birthday_prompt = tr(&lang, "ai_prompt_birthday", &CONFIG.ai_prompt_birthday);
+    let (prompt, version) = if is_birthday && !birthday_prompt.is_empty() {
+        (
+            render_text(
+                &birthday_prompt,
+                context! { username => user.username, weather },
+            ),
+            prompt_version(&birthday_prompt),
+        )
+    } else {
+        (prompt, version)
+    };
 
-    PLATFORM
+    let occasions = CALENDAR
         .get()
-        .expect("Failed to get platform instance")
-        .clone()
-        .send_message(
-            user.clone(),
-            &string_format!(
-                CONFIG.greeting_fmt.clone(),
-                user.username.clone(),
-                format_datetime_russian(now.naive_local()),
-                weather,
-                response.clone()
+        .expect("Failed to get calendar instance")
+        .occasions(today, &user.id)
+        .into_iter()
+        .map(|occasion| occasion.name)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let (prompt, occasions) = if occasions.is_empty() {
+        (prompt, String::new())
+    } else {
+        (
+            prompt
+                + &render_text(
+                    &tr(&lang, "ai_occasions_fmt", &CONFIG.ai_occasions_fmt),
+                    context! { occasions },
+                ),
+            render(
+                &tr(&lang, "occasions_fmt", &CONFIG.occasions_fmt),
+                context! { occasions },
             ),
         )
+    };
+
+    let ai_module = AI.get().expect("Failed to get AI instance").clone();
+    let response = ai_module.process(prompt).await.ok();
+
+    let greeting_id = match &response {
+        Some(text) => DB
+            .get()
+            .expect("Failed to get DB instance")
+            .save_greeting(GreetingModel {
+                user_id: user.id.clone(),
+                text: text.clone(),
+                model: ai_module.model(),
+                prompt_version: version,
+                date: today,
+            })
+            .await
+            .map_err(|err| tracing::error!("Failed to save greeting: {err}"))
+            .ok(),
+        None => None,
+    };
+
+    let ai_off = response.is_none();
+    let ai = response.map_or_else(
+        || safe(tr(&lang, "ai_msg_off", &CONFIG.ai_msg_off)),
+        Value::from,
+    );
+
+    let msg = render(
+        &greeting_fmt,
+        context! {
+            username => user.username,
+            date => formatter.format(now),
+            weather => safe(weather),
+            ai,
+            ai_off,
+            occasions => safe(occasions),
+        },
+    );
+
+    let buttons = greeting_buttons(&lang, greeting_id, user.id == CONFIG.channel);
+    send_greeting(user, &msg, media, &buttons).await;
+}
+
+/// Sends the greeting and tracks whether the user still can receive them.
+#[tracing::instrument]
+async fn send_greeting(
+    user: UserModel,
+    msg: &str,
+    media: Option<MediaModel>,
+    buttons: &[Vec<ButtonModel>],
+) {
+    let platform = PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone();
+    let user_id = user.id.clone();
+    let sent = match media {
+        Some(media) => {
+            platform
+                .clone()
+                .send_media(user, &media, msg, buttons)
+                .await
+        }
+        None => {
+            platform
+                .clone()
+                .send_message_with_buttons(user, msg, buttons)
+                .await
+        }
+    }
+    .map_err(|err| (platform.is_unreachable(err.as_ref()), err.to_string()));
+
+    // Users, who blocked the bot, shouldn't take part in draws until they get greetings again
+    let is_active = match sent {
+        Ok(()) => true,
+        Err((true, err)) => {
+            tracing::warn!("Greeting to {user_id} is blocked, marking inactive: {err}");
+            false
+        }
+        Err((false, err)) => {
+            tracing::warn!("Failed to send greeting to {user_id}: {err}");
+            return;
+        }
+    };
+    DB.get()
+        .expect("Failed to get DB instance")
+        .set_active(&user_id, is_active)
         .await
-        .expect("Send message failed");
+        .expect("Error accessing to database");
 }
 
+/// Greets users, who share language and location, so weather is requested once for them.
 #[tracing::instrument]
-pub async fn handle_daily_message() {
-    let weather_struct = WEATHER
+async fn process_group(lang: String, location: String, users: Vec<UserModel>, now: DateTime<Utc>) {
+    let (formatted_weather, template, prompt, media) = match WEATHER
         .get()
         .expect("Failed to get weather instance")
         .clone()
-        .get_weather()
+        .get_weather(&location, &lang)
         .await
-        .expect("Failed to get weather");
-
-    let formatted_weather = string_format!(
-        CONFIG.weather_fmt.clone(),
-        weather_struct.temp_c,
-        weather_struct.feels_like_c,
-        weather_struct.wind_speed_kmph,
-        weather_struct.min_temp_c,
-        weather_struct.max_temp_c,
-        weather_to_emoji(&weather_struct.status),
-        weather_struct.status
+    {
+        Ok(weather) => {
+            let formatted = format_weather(
+                &weather,
+                &tr(&lang, "weather_fmt", &CONFIG.weather_fmt),
+                &CONFIG.emoji,
+                &lang,
+            );
+            let template = tr(&lang, "ai_prompt", &CONFIG.ai_prompt);
+            let prompt = render_text(&template, context! { weather => formatted });
+            (
+                formatted,
+                template,
+                prompt,
+                CONFIG.media.media_for(weather.condition_code),
+            )
+        }
+        Err(err) => {
+            tracing::error!("Failed to get weather in {location}, greeting without it: {err}");
+            let unavailable = tr(
+                &lang,
+                "weather_unavailable_fmt",
+                &CONFIG.weather_unavailable_fmt,
+            );
+            let template = tr(&lang, "ai_prompt_no_weather", &CONFIG.ai_prompt_no_weather);
+            let (template, prompt) = if template.is_empty() {
+                let template = tr(&lang, "ai_prompt", &CONFIG.ai_prompt);
+                let prompt = render_text(&template, context! { weather => unavailable });
+                (template, prompt)
+            } else {
+                (template.clone(), template)
+            };
+            (unavailable, template, prompt, CONFIG.media.media_for(None))
+        }
+    };
+
+    for user in users {
+        tokio::spawn(process_user(
+            user,
+            lang.clone(),
+            formatted_weather.clone(),
+            prompt.clone(),
+            prompt_version(&template),
+            media.clone(),
+            now,
+        ));
+    }
+}
+
+/// Names of users, whose birthday is `today`.
+#[tracing::instrument(skip(users))]
+fn birthday_names(users: &[UserModel], today: NaiveDate) -> Vec<String> {
+    users
+        .iter()
+        .filter(|user| user.birthday.is_some_and(|birthday| birthday.is_on(today)))
+        .map(|user| user.username.clone())
+        .collect()
+}
+
+#[tracing::instrument]
+async fn announce_birthdays(channel: UserModel, users: &[UserModel], now: DateTime<Utc>) {
+    let fmt = tr(
+        &CONFIG.default_lang,
+        "birthday_channel_fmt",
+        &CONFIG.birthday_channel_fmt,
     );
+    let today = date_formatter_for(&CONFIG.default_lang).local_date(now);
+    let names = birthday_names(users, today);
+
+    if fmt.is_empty() || names.is_empty() {
+        return;
+    }
 
-    let users = DB
+    // Greetings are sent anyway, even if the announcement failed
+    if let Err(err) = PLATFORM
         .get()
-        .expect("Failed to get DB instance")
+        .expect("Failed to get platform instance")
         .clone()
-        .get_users()
+        .send_message(
+            channel,
+            &render(&fmt, context! { usernames => names.join(", ") }),
+        )
         .await
-        .expect("Error while getting users");
+    {
+        tracing::error!("Failed to announce birthdays: {err}");
+    }
+}
 
-    for user in users {
-        tokio::spawn(process_user(user, formatted_weather.clone()));
+/// Greets `recipients`, birthdays of users among them are announced if the channel is there too.
+#[tracing::instrument(skip(recipients))]
+pub async fn handle_daily_message(recipients: Vec<UserModel>, clock: &dyn Clock) {
+    // All greetings of the day have the same date, even if sending takes long
+    let now = clock.now();
+    if let Some(channel) = recipients.iter().find(|user| user.id == CONFIG.channel) {
+        announce_birthdays(channel.clone(), &recipients, now).await;
     }
 
-    let channel = UserModel {
-        id: CONFIG.channel.clone(),
-        username: "oneprogofficial".into(),
+    // Users without a city chosen via `/city` get weather of the default location
+    let mut groups: HashMap<(String, String), Vec<UserModel>> = HashMap::new();
+    for user in recipients {
+        let location = user
+            .location
+            .clone()
+            .unwrap_or_else(|| CONFIG.weather_location.clone());
+        groups
+            .entry((I18N.resolve_lang(user.lang.as_deref()), location))
+            .or_default()
+            .push(user);
+    }
+
+    for ((lang, location), users) in groups {
+        tokio::spawn(process_group(lang, location, users, now));
+    }
+}
+
+#[cfg(test)]
+mod daily_messages_tests {
+    use std::{
+        env, fs,
+        sync::{Arc, LazyLock},
+        time::Duration,
     };
 
-    tokio::spawn(process_user(channel, formatted_weather.clone()));
+    use chrono::{TimeDelta, TimeZone, Utc};
+    use chrono_tz::Tz;
+
+    use crate::{
+        AI, CALENDAR, DB, PLATFORM, WEATHER,
+        ai_mod::fake_ai::FakeAi,
+        calendar_mod::file_calendar::FileCalendar,
+        clock_mod::{clock::Clock, manual_clock::ManualClock},
+        db_mod::sqlite_database::SqliteDatabase,
+        handlers_mod::{
+            daily_messages_handler::{birthday_names, handle_daily_message},
+            date_handler::{DateFormatter, DateLocale},
+        },
+        models_mod::{birthday_model::BirthdayModel, user_model::UserModel},
+        platforms_mod::fake_platform::FakePlatform,
+        tools_mod::config_tools::CONFIG,
+        traits_mod::create_traits::Create,
+        weather_mod::fake_weather::FakeWeather,
+    };
+
+    static FAKE_PLATFORM: LazyLock<Arc<FakePlatform>> =
+        LazyLock::new(|| FakePlatform::new().expect("Failed to create fake platform"));
+
+    /// Sets fakes as global modules, database is a fresh temp file.
+    async fn init_modules() {
+        PLATFORM.get_or_init(|| FAKE_PLATFORM.clone());
+        AI.get_or_init(|| FakeAi::new().expect("Failed to create fake AI"));
+        WEATHER.get_or_init(|| FakeWeather::new().expect("Failed to create fake weather"));
+        CALENDAR.get_or_init(|| Arc::new(FileCalendar::default()));
+        if DB.get().is_none() {
+            let path = env::temp_dir().join(format!("greeting_daily_{}.db", std::process::id()));
+            let _ = fs::remove_file(&path);
+            let db = SqliteDatabase::connect(
+                &format!("sqlite://{}?mode=rwc", path.display()),
+                &CONFIG.channel,
+            )
+            .await
+            .expect("Failed to create database");
+            let _ = DB.set(db);
+        }
+    }
+
+    /// Waits for messages of the spawned greeting tasks.
+    async fn wait_sent_to(user_id: &str, count: usize) -> Vec<String> {
+        for _ in 0..500 {
+            let sent = FAKE_PLATFORM.sent_to(user_id);
+            if sent.len() >= count {
+                return sent;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("{user_id} didn't get {count} messages");
+    }
+
+    fn user(username: &str, birthday: Option<BirthdayModel>) -> UserModel {
+        UserModel {
+            id: username.into(),
+            username: username.into(),
+            location: None,
+            lang: None,
+            birthday,
+        }
+    }
+
+    #[test]
+    fn test_birthdays_at_local_midnight() {
+        let users = [
+            user("alice", Some(BirthdayModel { day: 18, month: 10 })),
+            user("bob", Some(BirthdayModel { day: 19, month: 10 })),
+            user("carol", None),
+        ];
+        let formatter = DateFormatter::new(DateLocale::Ru, Tz::Europe__Moscow, "");
+        let clock = ManualClock::at(
+            Utc.with_ymd_and_hms(2026, 10, 18, 20, 59, 0)
+                .single()
+                .expect("Invalid datetime"),
+        );
+        assert_eq!(
+            birthday_names(&users, formatter.local_date(clock.now())),
+            ["alice"]
+        );
+
+        // It's still October 18 in UTC, but already 19 in Moscow
+        clock.advance(TimeDelta::minutes(1));
+        assert_eq!(
+            birthday_names(&users, formatter.local_date(clock.now())),
+            ["bob"]
+        );
+        assert_eq!(
+            formatter.format(clock.now()),
+            "понедельник, 19 октября 2026 года, 00:00"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_daily_message_at_local_midnight() {
+        init_modules().await;
+        // It's still October 18 in UTC, but already 19 in Moscow
+        let clock = ManualClock::at(
+            Utc.with_ymd_and_hms(2026, 10, 18, 21, 0, 0)
+                .single()
+                .expect("Invalid datetime"),
+        );
+        let channel = UserModel {
+            id: CONFIG.channel.clone(),
+            ..user("channel", None)
+        };
+        let recipients = vec![
+            channel,
+            user("daily_bob", Some(BirthdayModel { day: 19, month: 10 })),
+            user("daily_carol", Some(BirthdayModel { day: 18, month: 10 })),
+        ];
+
+        handle_daily_message(recipients, &clock).await;
+
+        assert_eq!(
+            wait_sent_to("daily_bob", 1).await,
+            ["🎂 daily_bob, Москва: Хорошего дня!"]
+        );
+        assert_eq!(
+            wait_sent_to("daily_carol", 1).await,
+            ["понедельник, 19 октября 2026 года, 00:00, Москва: Хорошего дня!"]
+        );
+        assert_eq!(
+            wait_sent_to(&CONFIG.channel, 2).await,
+            [
+                "🎂 daily_bob",
+                "понедельник, 19 октября 2026 года, 00:00, Москва: Хорошего дня!"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_daily_message_in_users_cities() {
+        init_modules().await;
+        let clock = ManualClock::at(
+            Utc.with_ymd_and_hms(2026, 10, 19, 5, 0, 0)
+                .single()
+                .expect("Invalid datetime"),
+        );
+        let in_city = |id: &str, city: &str| UserModel {
+            location: Some(city.into()),
+            ..user(id, None)
+        };
+        let recipients = vec![
+            in_city("city_dave", "Казань"),
+            in_city("city_erin", "Сочи"),
+            user("city_frank", None),
+        ];
+
+        handle_daily_message(recipients, &clock).await;
+
+        let date = "понедельник, 19 октября 2026 года, 08:00";
+        assert_eq!(
+            wait_sent_to("city_dave", 1).await,
+            [format!("{date}, Казань: Хорошего дня!")]
+        );
+        assert_eq!(
+            wait_sent_to("city_erin", 1).await,
+            [format!("{date}, Сочи: Хорошего дня!")]
+        );
+        assert_eq!(
+            wait_sent_to("city_frank", 1).await,
+            [format!("{date}, Москва: Хорошего дня!")]
+        );
+    }
 }
@@ -1,58 +1,458 @@
-use chrono::{Datelike, NaiveDateTime, Timelike};
-
-const WEEKDAYS: [&str; 7] = [
-    "воскресенье",
-    "понедельник",
-    "вторник",
-    "среда",
-    "четверг",
-    "пятница",
-    "суббота",
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc};
+use chrono_tz::Tz;
+
+use minijinja::context;
+
+use crate::tools_mod::{config_tools::CONFIG, i18n_tools::tr, template_tools::render_text};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateLocale {
+    Ru,
+    Uk,
+    En,
+    De,
+}
+
+struct LocaleNames {
+    /// Starting from Sunday
+    weekdays: [&'static str; 7],
+    /// Form used after day number, e.g. "15 января"
+    months_genitive: [&'static str; 12],
+    /// Standalone form, e.g. "январь"
+    months_nominative: [&'static str; 12],
+    pattern: &'static str,
+}
+
+const RU: LocaleNames = LocaleNames {
+    weekdays: [
+        "воскресенье",
+        "понедельник",
+        "вторник",
+        "среда",
+        "четверг",
+        "пятница",
+        "суббота",
+    ],
+    months_genitive: [
+        "января",
+        "февраля",
+        "марта",
+        "апреля",
+        "мая",
+        "июня",
+        "июля",
+        "августа",
+        "сентября",
+        "октября",
+        "ноября",
+        "декабря",
+    ],
+    months_nominative: [
+        "январь",
+        "февраль",
+        "март",
+        "апрель",
+        "май",
+        "июнь",
+        "июль",
+        "август",
+        "сентябрь",
+        "октябрь",
+        "ноябрь",
+        "декабрь",
+    ],
+    pattern: "{{ weekday }}, {{ day }} {{ month }} {{ year }} года, {{ time }}",
+};
+
+const UK: LocaleNames = LocaleNames {
+    weekdays: [
+        "неділя",
+        "понеділок",
+        "вівторок",
+        "середа",
+        "четвер",
+        "пʼятниця",
+        "субота",
+    ],
+    months_genitive: [
+        "січня",
+        "лютого",
+        "березня",
+        "квітня",
+        "травня",
+        "червня",
+        "липня",
+        "серпня",
+        "вересня",
+        "жовтня",
+        "листопада",
+        "грудня",
+    ],
+    months_nominative: [
+        "січень",
+        "лютий",
+        "березень",
+        "квітень",
+        "травень",
+        "червень",
+        "липень",
+        "серпень",
+        "вересень",
+        "жовтень",
+        "листопад",
+        "грудень",
+    ],
+    pattern: "{{ weekday }}, {{ day }} {{ month }} {{ year }} року, {{ time }}",
+};
+
+const EN_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
 ];
 
-const MONTHS: [&str; 13] = [
-    "",
-    "января",
-    "февраля",
-    "марта",
-    "апреля",
-    "мая",
-    "июня",
-    "июля",
-    "августа",
-    "сентября",
-    "октября",
-    "ноября",
-    "декабря",
+const EN: LocaleNames = LocaleNames {
+    weekdays: [
+        "Sunday",
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+    ],
+    months_genitive: EN_MONTHS,
+    months_nominative: EN_MONTHS,
+    pattern: "{{ weekday }}, {{ month }} {{ day_ord }}, {{ year }}, {{ time }}",
+};
+
+const DE_MONTHS: [&str; 12] = [
+    "Januar",
+    "Februar",
+    "März",
+    "April",
+    "Mai",
+    "Juni",
+    "Juli",
+    "August",
+    "September",
+    "Oktober",
+    "November",
+    "Dezember",
 ];
 
+const DE: LocaleNames = LocaleNames {
+    weekdays: [
+        "Sonntag",
+        "Montag",
+        "Dienstag",
+        "Mittwoch",
+        "Donnerstag",
+        "Freitag",
+        "Samstag",
+    ],
+    months_genitive: DE_MONTHS,
+    months_nominative: DE_MONTHS,
+    pattern: "{{ weekday }}, {{ day_ord }} {{ month }} {{ year }}, {{ time }}",
+};
+
+impl DateLocale {
+    #[tracing::instrument]
+    pub fn from_lang(lang: &str) -> Option<Self> {
+        match lang {
+            "ru" => Some(Self::Ru),
+            "uk" => Some(Self::Uk),
+            "en" => Some(Self::En),
+            "de" => Some(Self::De),
+            _ => None,
+        }
+    }
+
+    const fn names(self) -> &'static LocaleNames {
+        match self {
+            Self::Ru => &RU,
+            Self::Uk => &UK,
+            Self::En => &EN,
+            Self::De => &DE,
+        }
+    }
+
+    fn ordinal(self, day: u32) -> String {
+        match self {
+            Self::Ru | Self::Uk => day.to_string(),
+            Self::De => format!("{day}."),
+            Self::En => {
+                let suffix = match (day % 10, day % 100) {
+                    (_, 11..=13) => "th",
+                    (1, _) => "st",
+                    (2, _) => "nd",
+                    (3, _) => "rd",
+                    _ => "th",
+                };
+                format!("{day}{suffix}")
+            }
+        }
+    }
+}
+
+/// Formats dates in given locale and timezone.
+///
+/// Pattern is a template with [`crate::tools_mod::template_tools::DATE_VARS`]: `weekday`, `day`,
+/// `day_ord` (ordinal day, e.g. `15th` or `15.`), `month` (form used with day), `month_nom`
+/// (standalone form), `month_num`, `year`, `hour`, `minute` and `time` (`hour:minute`).
+#[derive(Debug, Clone)]
+pub struct DateFormatter {
+    locale: DateLocale,
+    tz: Tz,
+    pattern: String,
+}
+
+impl DateFormatter {
+    /// Empty pattern means the default one of the locale.
+    #[tracing::instrument]
+    pub fn new(locale: DateLocale, tz: Tz, pattern: &str) -> Self {
+        let pattern = if pattern.is_empty() {
+            locale.names().pattern
+        } else {
+            pattern
+        };
+        Self {
+            locale,
+            tz,
+            pattern: pattern.into(),
+        }
+    }
+
+    #[tracing::instrument]
+    pub fn local_date(&self, dt: DateTime<Utc>) -> NaiveDate {
+        dt.with_timezone(&self.tz).date_naive()
+    }
+
+    #[tracing::instrument]
+    pub fn format(&self, dt: DateTime<Utc>) -> String {
+        let local = dt.with_timezone(&self.tz);
+        let names = self.locale.names();
+        let hh = format!("{:02}", local.hour());
+        let mm = format!("{:02}", local.minute());
+
+        render_text(
+            &self.pattern,
+            context! {
+                weekday => names.weekdays[local.weekday().num_days_from_sunday() as usize],
+                day => local.day(),
+                day_ord => self.locale.ordinal(local.day()),
+                month => names.months_genitive[local.month0() as usize],
+                month_nom => names.months_nominative[local.month0() as usize],
+                month_num => format!("{:02}", local.month()),
+                year => local.year(),
+                time => format!("{hh}:{mm}"),
+                hour => hh,
+                minute => mm,
+            },
+        )
+    }
+}
+
+/// Creates formatter for language with timezone and pattern from config.
+/// Unsupported languages fall back to default language, then to Russian.
 #[tracing::instrument]
-pub fn format_datetime_russian(dt: NaiveDateTime) -> String {
-    let weekday = WEEKDAYS[dt.weekday().num_days_from_sunday() as usize];
-    let day = dt.day();
-    let month = MONTHS[dt.month() as usize];
-    let year = dt.year();
-    let hour = dt.hour() + 3;
-    let min = dt.minute();
-
-    format!("{weekday}, {day} {month} {year} года, {hour:02}:{min:02}")
+pub fn date_formatter_for(lang: &str) -> DateFormatter {
+    let locale = DateLocale::from_lang(lang)
+        .or_else(|| DateLocale::from_lang(&CONFIG.default_lang))
+        .unwrap_or(DateLocale::Ru);
+    DateFormatter::new(
+        locale,
+        CONFIG.timezone,
+        &tr(lang, "date_fmt", &CONFIG.date_fmt),
+    )
 }
 
 #[cfg(test)]
 mod date_tests {
-    use chrono::NaiveDate;
+    use chrono::{DateTime, NaiveDate, Utc};
+    use chrono_tz::Tz;
 
-    use crate::handlers_mod::date_handler::format_datetime_russian;
+    use crate::handlers_mod::date_handler::{DateFormatter, DateLocale};
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .expect("Failed to create datetime")
+            .and_hms_opt(h, min, 0)
+            .expect("Failed to add hours, minutes and seconds to date")
+            .and_utc()
+    }
 
     #[test]
     fn test_format_datetime_russian() {
-        let date = NaiveDate::from_ymd_opt(2026, 1, 15)
-            .expect("Failed to create datetime")
-            .and_hms_opt(3, 1, 0)
-            .expect("Failed to add hours, minutes and seconds to date");
+        let formatter = DateFormatter::new(DateLocale::Ru, Tz::Europe__Moscow, "");
         assert_eq!(
-            format_datetime_russian(date),
+            formatter.format(utc(2026, 1, 15, 3, 1)),
             "четверг, 15 января 2026 года, 06:01"
         );
     }
+
+    #[test]
+    fn test_format_locales() {
+        let cases = [
+            (
+                DateLocale::Ru,
+                Tz::Europe__Moscow,
+                "пятница, 1 мая 2026 года, 12:00",
+            ),
+            (
+                DateLocale::Uk,
+                Tz::Europe__Kyiv,
+                "пʼятниця, 1 травня 2026 року, 12:00",
+            ),
+            (
+                DateLocale::En,
+                Tz::Europe__London,
+                "Friday, May 1st, 2026, 10:00",
+            ),
+            (
+                DateLocale::De,
+                Tz::Europe__Berlin,
+                "Freitag, 1. Mai 2026, 11:00",
+            ),
+        ];
+        for (locale, tz, expected) in cases {
+            let formatter = DateFormatter::new(locale, tz, "");
+            assert_eq!(formatter.format(utc(2026, 5, 1, 9, 0)), expected);
+        }
+    }
+
+    #[test]
+    fn test_format_english_ordinals() {
+        let formatter = DateFormatter::new(DateLocale::En, Tz::UTC, "{{ day_ord }}");
+        let cases = [
+            (1, "1st"),
+            (2, "2nd"),
+            (3, "3rd"),
+            (4, "4th"),
+            (11, "11th"),
+            (12, "12th"),
+            (13, "13th"),
+            (21, "21st"),
+            (22, "22nd"),
+            (23, "23rd"),
+            (31, "31st"),
+        ];
+        for (day, expected) in cases {
+            assert_eq!(formatter.format(utc(2026, 1, day, 0, 0)), expected);
+        }
+    }
+
+    #[test]
+    fn test_format_custom_pattern() {
+        let cases = [
+            (DateLocale::Ru, "март, 15 марта"),
+            (DateLocale::Uk, "березень, 15 березня"),
+            (DateLocale::En, "March, 15 March"),
+            (DateLocale::De, "März, 15 März"),
+        ];
+        for (locale, expected) in cases {
+            let formatter =
+                DateFormatter::new(locale, Tz::UTC, "{{ month_nom }}, {{ day }} {{ month }}");
+            assert_eq!(formatter.format(utc(2026, 3, 15, 12, 0)), expected);
+        }
+    }
+
+    #[test]
+    fn test_format_dst_transitions() {
+        let cases = [
+            // Europe/Berlin switches to CEST at 01:00 UTC on 29 March 2026
+            (
+                Tz::Europe__Berlin,
+                utc(2026, 3, 29, 0, 59),
+                "29.03.2026 01:59",
+            ),
+            (
+                Tz::Europe__Berlin,
+                utc(2026, 3, 29, 1, 0),
+                "29.03.2026 03:00",
+            ),
+            // And back to CET at 01:00 UTC on 25 October 2026, so 02:xx happens twice
+            (
+                Tz::Europe__Berlin,
+                utc(2026, 10, 25, 0, 59),
+                "25.10.2026 02:59",
+            ),
+            (
+                Tz::Europe__Berlin,
+                utc(2026, 10, 25, 1, 0),
+                "25.10.2026 02:00",
+            ),
+            // America/New_York switches to EDT at 07:00 UTC on 8 March 2026
+            (
+                Tz::America__New_York,
+                utc(2026, 3, 8, 6, 59),
+                "8.03.2026 01:59",
+            ),
+            (
+                Tz::America__New_York,
+                utc(2026, 3, 8, 7, 0),
+                "8.03.2026 03:00",
+            ),
+            // Moscow has no DST
+            (Tz::Europe__Moscow, utc(2026, 7, 1, 0, 0), "1.07.2026 03:00"),
+            (
+                Tz::Europe__Moscow,
+                utc(2026, 12, 1, 0, 0),
+                "1.12.2026 03:00",
+            ),
+        ];
+        for (tz, dt, expected) in cases {
+            let formatter = DateFormatter::new(
+                DateLocale::En,
+                tz,
+                "{{ day }}.{{ month_num }}.{{ year }} {{ time }}",
+            );
+            assert_eq!(formatter.format(dt), expected);
+        }
+    }
+
+    #[test]
+    fn test_format_midnight_boundaries() {
+        let cases = [
+            (
+                utc(2026, 1, 14, 20, 59),
+                "среда, 14 января 2026 года, 23:59",
+            ),
+            (
+                utc(2026, 1, 14, 21, 0),
+                "четверг, 15 января 2026 года, 00:00",
+            ),
+            (
+                utc(2025, 12, 31, 20, 59),
+                "среда, 31 декабря 2025 года, 23:59",
+            ),
+            (
+                utc(2025, 12, 31, 21, 0),
+                "четверг, 1 января 2026 года, 00:00",
+            ),
+            (
+                utc(2028, 2, 28, 21, 0),
+                "вторник, 29 февраля 2028 года, 00:00",
+            ),
+        ];
+        let formatter = DateFormatter::new(DateLocale::Ru, Tz::Europe__Moscow, "");
+        for (dt, expected) in cases {
+            assert_eq!(formatter.format(dt), expected);
+        }
+    }
+
+    #[test]
+    fn test_locale_from_lang() {
+        assert_eq!(DateLocale::from_lang("uk"), Some(DateLocale::Uk));
+        assert_eq!(DateLocale::from_lang("fr"), None);
+    }
 }
@@ -1,50 +1,293 @@
-use rand::random_range;
+use std::{iter, sync::Arc};
 
-use crate::{DB, PLATFORM, models_mod::user_model::UserModel, tools_mod::config_tools::CONFIG};
-use string_format::string_format;
+use chrono::NaiveDate;
 
-pub async fn handle_draw() {
-    let users = DB
-        .get()
-        .expect("Failed to get DB instance")
-        .clone()
-        .get_users()
+use crate::{
+    DB, PLATFORM,
+    clock_mod::clock::Clock,
+    db_mod::database::DatabaseModule,
+    handlers_mod::{date_handler::date_formatter_for, lang_handler::user_lang},
+    models_mod::{
+        draw_model::{DrawModel, DrawWinnerModel},
+        prize_model::PrizeModel,
+        user_model::UserModel,
+    },
+    platforms_mod::platform::PlatformModule,
+    tools_mod::{
+        config_tools::CONFIG,
+        fair_draw_tools::{commitment, fair_pick},
+        i18n_tools::{I18N, tr},
+        template_tools::render,
+    },
+};
+use minijinja::{Value, context};
+
+const DRAWS_HISTORY_LEN: u32 = 10;
+
+const DEFAULT_DRAWS_FMT: &str = "Последние розыгрыши:\
+    {% for draw in draws %}\n{{ draw.date }}: {{ draw.username }} \
+    из {{ draw.participants }}, seed <code>{{ draw.seed }}</code>\
+    {% else %}\nрозыгрышей ещё не было{% endfor %}";
+
+// Seed is revealed with results, so anyone can repeat the draw with `verify-draw`
+const DEFAULT_DRAW_VERIFY_FMT: &str = "Сид: <code>{{ seed }}</code>\n\
+    Хеш сида: <code>{{ commitment }}</code>\n\
+    Участники: {{ participant_ids|join(\", \") }}";
+
+/// Candidates in the order of [`fair_pick`], as they are published for verification:
+/// `<id>` with one ticket or `<id>:<tickets>`.
+fn published_participants(candidates: &[(&UserModel, u64)]) -> Vec<String> {
+    let mut sorted = candidates.to_vec();
+    sorted.sort_unstable_by(|(first, _), (second, _)| first.id.cmp(&second.id));
+    sorted
+        .into_iter()
+        .map(|(user, tickets)| {
+            if tickets == 1 {
+                user.id.clone()
+            } else {
+                format!("{}:{tickets}", user.id)
+            }
+        })
+        .collect()
+}
+
+/// Draw tickets of user by `draw_weights` formula of the config.
+async fn user_tickets(db: &Arc<dyn DatabaseModule>, user_id: &str, today: NaiveDate) -> u64 {
+    db.get_engagement(user_id)
         .await
-        .expect("Error while getting users");
+        .expect("Error accessing to database")
+        .tickets(&CONFIG.draw_weights, today)
+}
 
-    let mut ind = random_range(0..users.len());
-    let mut choice = &users[ind];
+/// Users, who can win: everyone except the admin and the `excluded` ones.
+#[tracing::instrument(skip(users))]
+pub fn eligible_candidates<'a>(
+    users: &'a [UserModel],
+    admin: &str,
+    excluded: &[&[String]],
+) -> Vec<&'a UserModel> {
+    users
+        .iter()
+        .filter(|user| user.id != admin)
+        .filter(|user| !excluded.iter().any(|ids| ids.contains(&user.id)))
+        .collect()
+}
 
-    let mut it = 0;
+/// Picks winners of the prizes without replacement, so nobody wins twice, with
+/// [`fair_pick`], so the draw can be verified by the seed and candidates with their tickets.
+/// If there are fewer candidates than prizes, the top tiers are filled first.
+pub fn pick_winners<'a, 'p>(
+    candidates: &[(&'a UserModel, u64)],
+    prizes: &'p [PrizeModel],
+    seed: &str,
+) -> Vec<(&'p PrizeModel, &'a UserModel)> {
+    let total = prizes.iter().map(|prize| prize.count as usize).sum();
+    let participants = candidates
+        .iter()
+        .map(|(user, tickets)| (user.id.as_str(), *tickets))
+        .collect::<Vec<_>>();
+    let winners = fair_pick(seed, &participants, total)
+        .into_iter()
+        .filter_map(|id| {
+            candidates
+                .iter()
+                .find(|(user, _)| user.id == id)
+                .map(|(user, _)| *user)
+        });
+    prizes
+        .iter()
+        .flat_map(|prize| iter::repeat_n(prize, prize.count as usize))
+        .zip(winners)
+        .collect()
+}
 
-    while choice.id == CONFIG.admin {
-        ind = random_range(0..users.len());
-        choice = &users[ind];
-        it += 1;
-        assert!(it != 10_000_000, "Unluckly, can't choose the winner");
+/// Prize tiers with usernames of their winners for `draw_results_fmt`, tiers without winners
+/// are skipped.
+fn results_prizes(prizes: &[PrizeModel], winners: &[(&PrizeModel, &UserModel)]) -> Vec<Value> {
+    prizes
+        .iter()
+        .filter_map(|prize| {
+            let usernames = winners
+                .iter()
+                .filter(|(won, _)| std::ptr::eq(*won, prize))
+                .map(|(_, user)| user.username.clone())
+                .collect::<Vec<_>>();
+            (!usernames.is_empty()).then(|| context! { name => prize.name, winners => usernames })
+        })
+        .collect()
+}
+
+/// Sends each winner the message of their prize tier.
+async fn notify_winners(platform: &Arc<dyn PlatformModule>, winners: &[(&PrizeModel, &UserModel)]) {
+    for (prize, user) in winners {
+        let win_fmt = if prize.win_fmt.is_empty() {
+            tr(
+                &I18N.resolve_lang(user.lang.as_deref()),
+                "draw_win_fmt",
+                &CONFIG.draw_win_fmt,
+            )
+        } else {
+            prize.win_fmt.clone()
+        };
+        // One blocked winner shouldn't stop notifying the others
+        if let Err(err) = platform
+            .clone()
+            .send_message(
+                (*user).clone(),
+                &render(
+                    &win_fmt,
+                    context! { username => user.username, prize => prize.name },
+                ),
+            )
+            .await
+        {
+            tracing::error!("Failed to notify winner {}: {err}", user.id);
+        }
+    }
+}
+
+/// Results of the draw followed by revealed seed and candidate ids for verification.
+fn results_message(
+    prizes: &[PrizeModel],
+    winners: &[(&PrizeModel, &UserModel)],
+    candidates: &[(&UserModel, u64)],
+    seed: &str,
+) -> String {
+    let results = render(
+        &tr(
+            &CONFIG.default_lang,
+            "draw_results_fmt",
+            &CONFIG.draw_results_fmt,
+        ),
+        context! {
+            username => winners[0].1.username,
+            participants => candidates.len(),
+            prizes => results_prizes(prizes, winners),
+        },
+    );
+    let verify = render(
+        &tr(
+            &CONFIG.default_lang,
+            "draw_verify_fmt",
+            DEFAULT_DRAW_VERIFY_FMT,
+        ),
+        context! {
+            seed,
+            commitment => commitment(seed),
+            participant_ids => published_participants(candidates),
+        },
+    );
+    format!("{results}\n\n{verify}")
+}
+
+/// Eligible users with their tickets: registered participants except the admin,
+/// `draw_excluded_ids`, recent winners and inactive users.
+pub async fn weighted_candidates<'a>(
+    db: &Arc<dyn DatabaseModule>,
+    users: &'a [UserModel],
+    today: NaiveDate,
+) -> Vec<(&'a UserModel, u64)> {
+    let recent_winners = db
+        .get_draws(CONFIG.draw_exclude_last)
+        .await
+        .expect("Error accessing to database")
+        .into_iter()
+        .flat_map(|draw| draw.winners)
+        .map(|winner| winner.user_id)
+        .collect::<Vec<_>>();
+    let inactive = db
+        .get_inactive_users()
+        .await
+        .expect("Error accessing to database");
+
+    let mut candidates = vec![];
+    for user in eligible_candidates(
+        users,
+        &CONFIG.admin,
+        &[&CONFIG.draw_excluded_ids, &recent_winners, &inactive],
+    ) {
+        candidates.push((user, user_tickets(db, &user.id, today).await));
     }
+    candidates
+}
+
+/// Closes the open giveaway, so later joins are rejected, and returns its seed and participants.
+/// Only registered users take part, so there are none if no giveaway was announced.
+async fn close_registration(db: &Arc<dyn DatabaseModule>) -> (String, Vec<UserModel>) {
+    let Some(giveaway) = db
+        .get_open_giveaway()
+        .await
+        .expect("Error accessing to database")
+    else {
+        return (String::new(), vec![]);
+    };
+    db.close_giveaway(giveaway.id)
+        .await
+        .expect("Error accessing to database");
+    let users = db
+        .get_giveaway_participants(giveaway.id)
+        .await
+        .expect("Error accessing to database");
+    (giveaway.seed, users)
+}
+
+pub async fn handle_draw(clock: &dyn Clock) {
+    let db = DB.get().expect("Failed to get DB instance").clone();
+    let (seed, users) = close_registration(&db).await;
+    let today = date_formatter_for(&CONFIG.default_lang).local_date(clock.now());
+    let candidates = weighted_candidates(&db, &users, today).await;
 
     let platform = PLATFORM
         .get()
         .expect("Failed to get platform instance")
         .clone();
 
-    platform
-        .clone()
-        .send_message(
-            choice.clone(),
-            &string_format!(CONFIG.draw_win_fmt.clone(), choice.username.clone()),
-        )
-        .await
-        .expect("Send message failed");
-
-    let results_fmt = &string_format!(CONFIG.draw_results_fmt.clone(), choice.username.clone());
-
     let admin = UserModel {
         id: CONFIG.clone().admin,
         username: "admin".into(),
+        location: None,
+        lang: None,
+        birthday: None,
     };
 
+    let prizes = CONFIG.prizes();
+    let winners = pick_winners(&candidates, &prizes, &seed);
+    if winners.is_empty() {
+        tracing::warn!("No eligible participants among {} registered", users.len());
+        platform
+            .send_message(
+                admin,
+                &tr(
+                    &CONFIG.default_lang,
+                    "draw_no_participants",
+                    "Розыгрыш не проведён: нет подходящих участников",
+                ),
+            )
+            .await
+            .expect("Send message failed");
+        return;
+    }
+
+    db.save_draw(DrawModel {
+        date: today,
+        winners: winners
+            .iter()
+            .map(|(prize, user)| DrawWinnerModel {
+                prize: prize.name.clone(),
+                user_id: user.id.clone(),
+                username: user.username.clone(),
+            })
+            .collect(),
+        participants: u32::try_from(candidates.len()).unwrap_or(u32::MAX),
+        seed: seed.clone(),
+    })
+    .await
+    .expect("Error accessing to database");
+
+    notify_winners(&platform, &winners).await;
+
+    let results_fmt = &results_message(&prizes, &winners, &candidates, &seed);
+
     platform
         .clone()
         .send_message(admin, results_fmt)
@@ -54,6 +297,9 @@ pub async fn handle_draw() {
     let channel: UserModel = UserModel {
         id: CONFIG.clone().channel,
         username: "channel".into(),
+        location: None,
+        lang: None,
+        birthday: None,
     };
 
     platform
@@ -61,3 +307,247 @@ pub async fn handle_draw() {
         .await
         .expect("Send message failed");
 }
+
+/// Shows admin the history of last draws.
+#[tracing::instrument]
+pub async fn handle_draws(user: UserModel) {
+    let platform = PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone();
+    let lang = user_lang(&user).await;
+
+    if user.id != CONFIG.admin {
+        platform
+            .send_message(
+                user,
+                &tr(&lang, "admin_only", "Команда доступна только админу"),
+            )
+            .await
+            .expect("Failed to send message");
+        return;
+    }
+
+    let draws = DB
+        .get()
+        .expect("Failed to get DB instance")
+        .get_draws(DRAWS_HISTORY_LEN)
+        .await
+        .expect("Error accessing to database")
+        .into_iter()
+        .map(|draw| {
+            context! {
+                date => draw.date.format("%d.%m.%Y").to_string(),
+                username => draw
+                    .winners
+                    .iter()
+                    .map(|winner| winner.username.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                participants => draw.participants,
+                seed => draw.seed,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    platform
+        .send_message(
+            user,
+            &render(
+                &tr(&lang, "draws_fmt", DEFAULT_DRAWS_FMT),
+                context! { draws },
+            ),
+        )
+        .await
+        .expect("Failed to send message");
+}
+
+#[cfg(test)]
+mod draw_tests {
+    use minijinja::context;
+
+    use crate::{
+        handlers_mod::draw_handler::{
+            DEFAULT_DRAW_VERIFY_FMT, DEFAULT_DRAWS_FMT, eligible_candidates, pick_winners,
+            published_participants, results_prizes,
+        },
+        models_mod::{prize_model::PrizeModel, user_model::UserModel},
+        tools_mod::template_tools::{check_template, render},
+    };
+
+    fn user(id: &str) -> UserModel {
+        UserModel {
+            id: id.into(),
+            username: format!("user {id}"),
+            location: None,
+            lang: None,
+            birthday: None,
+        }
+    }
+
+    fn ids(users: &[&UserModel]) -> Vec<String> {
+        users.iter().map(|user| user.id.clone()).collect()
+    }
+
+    fn one_ticket<'a>(candidates: &[&'a UserModel]) -> Vec<(&'a UserModel, u64)> {
+        candidates.iter().map(|user| (*user, 1)).collect()
+    }
+
+    fn prize(name: &str, count: u32) -> PrizeModel {
+        PrizeModel {
+            name: name.into(),
+            count,
+            win_fmt: String::new(),
+        }
+    }
+
+    fn winner_ids(winners: &[(&PrizeModel, &UserModel)]) -> Vec<(String, String)> {
+        winners
+            .iter()
+            .map(|(prize, user)| (prize.name.clone(), user.id.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_no_users() {
+        let candidates = eligible_candidates(&[], "admin", &[]);
+        assert!(candidates.is_empty());
+        assert!(pick_winners(&one_ticket(&candidates), &[prize("", 1)], "1").is_empty());
+    }
+
+    #[test]
+    fn test_only_admin() {
+        let users = [user("admin")];
+        let candidates = eligible_candidates(&users, "admin", &[]);
+        assert!(pick_winners(&one_ticket(&candidates), &[prize("", 1)], "1").is_empty());
+    }
+
+    #[test]
+    fn test_excluded_users() {
+        let users = [user("admin"), user("1"), user("2"), user("3"), user("4")];
+        let candidates = eligible_candidates(
+            &users,
+            "admin",
+            &[&["1".into()], &["2".into()], &["3".into(), "5".into()]],
+        );
+        assert_eq!(ids(&candidates), ["4"]);
+        assert_eq!(
+            winner_ids(&pick_winners(
+                &one_ticket(&candidates),
+                &[prize("", 1)],
+                "1"
+            )),
+            [(String::new(), "4".into())]
+        );
+    }
+
+    #[test]
+    fn test_all_users_excluded() {
+        // Recent winners and excluded ids cover everyone, so nobody wins instead of retrying forever
+        let users = [user("admin"), user("1"), user("2")];
+        let candidates = eligible_candidates(&users, "admin", &[&["1".into()], &["2".into()]]);
+        assert!(candidates.is_empty());
+        assert!(pick_winners(&one_ticket(&candidates), &[prize("", 2)], "1").is_empty());
+    }
+
+    #[test]
+    fn test_pick_winners_is_reproducible() {
+        let users = (0..100).map(|id| user(&id.to_string())).collect::<Vec<_>>();
+        let candidates = eligible_candidates(&users, "admin", &[]);
+        let prizes = [prize("1st", 1), prize("2nd", 2)];
+        let first = winner_ids(&pick_winners(&one_ticket(&candidates), &prizes, "42"));
+        let second = winner_ids(&pick_winners(&one_ticket(&candidates), &prizes, "42"));
+        assert_eq!(first.len(), 3);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_pick_winners_without_replacement() {
+        let users = (0..5).map(|id| user(&id.to_string())).collect::<Vec<_>>();
+        let candidates = eligible_candidates(&users, "admin", &[]);
+        let prizes = [prize("1st", 1), prize("2nd", 1), prize("3rd", 3)];
+        let winners = pick_winners(&one_ticket(&candidates), &prizes, "7");
+
+        let mut ids = winners.iter().map(|(_, user)| &user.id).collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, ["0", "1", "2", "3", "4"]);
+        assert_eq!(
+            winners
+                .iter()
+                .map(|(prize, _)| prize.name.as_str())
+                .collect::<Vec<_>>(),
+            ["1st", "2nd", "3rd", "3rd", "3rd"]
+        );
+    }
+
+    #[test]
+    fn test_pick_winners_fills_top_tiers() {
+        let users = [user("1"), user("2")];
+        let candidates = eligible_candidates(&users, "admin", &[]);
+        let prizes = [prize("1st", 1), prize("2nd", 2), prize("3rd", 1)];
+        let winners = pick_winners(&one_ticket(&candidates), &prizes, "7");
+        assert_eq!(
+            winners
+                .iter()
+                .map(|(prize, _)| prize.name.as_str())
+                .collect::<Vec<_>>(),
+            ["1st", "2nd"]
+        );
+
+        let results = results_prizes(&prizes, &winners);
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            render(
+                "{% for prize in prizes %}{{ prize.name }}: {{ prize.winners|length }};{% endfor %}",
+                context! { prizes => results }
+            ),
+            "1st: 1;2nd: 1;"
+        );
+    }
+
+    #[test]
+    fn test_default_draws_fmt() {
+        assert!(check_template(DEFAULT_DRAWS_FMT, &["draws"]).is_ok());
+
+        let draws = vec![context! {
+            date => "19.10.2026",
+            username => "bob",
+            participants => 12,
+            seed => "42",
+        }];
+        assert_eq!(
+            render(DEFAULT_DRAWS_FMT, context! { draws }),
+            "Последние розыгрыши:\n19.10.2026: bob из 12, seed <code>42</code>"
+        );
+    }
+
+    #[test]
+    fn test_default_draw_verify_fmt() {
+        assert!(
+            check_template(
+                DEFAULT_DRAW_VERIFY_FMT,
+                &["seed", "commitment", "participant_ids"]
+            )
+            .is_ok()
+        );
+        assert_eq!(
+            render(
+                DEFAULT_DRAW_VERIFY_FMT,
+                context! { seed => "s", commitment => "c", participant_ids => ["1", "2"] }
+            ),
+            "Сид: <code>s</code>\nХеш сида: <code>c</code>\nУчастники: 1, 2"
+        );
+    }
+
+    #[test]
+    fn test_weighted_candidates() {
+        let users = [user("2"), user("1"), user("3")];
+        let candidates = vec![(&users[0], 3), (&users[1], 1), (&users[2], 0)];
+        assert_eq!(published_participants(&candidates), ["1", "2:3", "3:0"]);
+
+        let prizes = [prize("", 3)];
+        let winners = pick_winners(&candidates, &prizes, "seed");
+        assert_eq!(winners.len(), 2);
+        assert!(winners.iter().all(|(_, user)| user.id != "3"));
+    }
+}
@@ -1,28 +1,76 @@
+use std::collections::HashSet;
+
+use minijinja::context;
+
+use crate::{
+    models_mod::{
+        emoji_model::{EmojiConfig, EmojiOrder},
+        weather_model::WeatherModel,
+    },
+    tools_mod::template_tools::render,
+};
+
 #[tracing::instrument]
-pub fn weather_to_emoji(desc: &str) -> String {
+pub fn format_weather(
+    weather: &WeatherModel,
+    fmt: &str,
+    emoji: &EmojiConfig,
+    lang: &str,
+) -> String {
+    render(
+        fmt,
+        context! {
+            temp_c => weather.temp_c,
+            feels_like_c => weather.feels_like_c,
+            wind_speed_kmph => weather.wind_speed_kmph,
+            min_temp_c => weather.min_temp_c,
+            max_temp_c => weather.max_temp_c,
+            emoji => weather_to_emoji(&weather.status, weather.condition_code, emoji, lang),
+            status => weather.status,
+            is_stale => weather.is_stale,
+        },
+    )
+}
+
+#[tracing::instrument]
+pub fn weather_to_emoji(desc: &str, code: Option<u32>, emoji: &EmojiConfig, lang: &str) -> String {
+    let Some(mapping) = emoji
+        .languages
+        .get(lang)
+        .or_else(|| emoji.languages.get(&emoji.lang))
+    else {
+        return String::new();
+    };
+
+    if let Some(by_code) = code.and_then(|code| mapping.codes.get(&code.to_string())) {
+        return by_code.clone();
+    }
+
     let desc_lower = desc.to_lowercase();
 
-    let patterns = vec![
-        ("замерзающий", "🧊"),
-        ("переменная", "☀️"),
-        ("гроза", "⛈️"),
-        ("дождь", "🌧️"),
-        ("снег", "❄️"),
-        ("слякоть", "🌨️"),
-        ("град", "🌨️"),
-        ("туман", "🌫️"),
-        ("дымка", "🌫️"),
-        ("ясно", "☀️"),
-        ("облачно", "☁️"),
-        ("пасмурно", "🌥️"),
-    ];
+    let mut found: Vec<(usize, &str)> = mapping
+        .patterns
+        .iter()
+        .filter_map(|(pattern, emoji)| {
+            desc_lower
+                .find(&pattern.to_lowercase())
+                .map(|pos| (pos, emoji.as_str()))
+        })
+        .collect();
+
+    if emoji.order == EmojiOrder::Text {
+        // Stable sort keeps table order for emojis of the same word
+        found.sort_by_key(|(pos, _)| *pos);
+    }
 
+    let mut seen = HashSet::new();
     let mut ans = String::new();
 
-    for (pattern, emoji) in &patterns {
-        if desc_lower.contains(pattern) {
-            ans += emoji;
+    for (_, emoji_str) in found {
+        if emoji.dedup && !seen.insert(emoji_str) {
+            continue;
         }
+        ans += emoji_str;
     }
 
     ans
@@ -30,55 +78,136 @@ pub fn weather_to_emoji(desc: &str) -> String {
 
 #[cfg(test)]
 mod weather_tests {
-    use crate::handlers_mod::formats_handler::weather_to_emoji;
+    use std::collections::HashMap;
+
+    use crate::{
+        handlers_mod::formats_handler::{format_weather, weather_to_emoji},
+        models_mod::{
+            emoji_model::{EmojiConfig, EmojiMapping, EmojiOrder},
+            weather_model::WeatherModel,
+        },
+    };
+
+    fn emoji(desc: &str) -> String {
+        weather_to_emoji(desc, None, &EmojiConfig::default(), "ru")
+    }
+
+    #[test]
+    fn test_format_weather() {
+        let weather = WeatherModel {
+            temp_c: "-3".into(),
+            feels_like_c: "-7".into(),
+            wind_speed_kmph: "12".into(),
+            min_temp_c: "-5".into(),
+            max_temp_c: "-1".into(),
+            status: "Снег".into(),
+            condition_code: Some(338),
+            is_stale: false,
+        };
+        assert_eq!(
+            format_weather(
+                &weather,
+                "{{ temp_c }}°C ({{ feels_like_c }}°C), {{ wind_speed_kmph }} км/ч, {{ min_temp_c }}..{{ max_temp_c }}°C, {{ emoji }} {{ status }}{% if is_stale %} (?){% endif %}",
+                &EmojiConfig::default(),
+                "ru"
+            ),
+            "-3°C (-7°C), 12 км/ч, -5..-1°C, ❄️ Снег"
+        );
+    }
 
     #[test]
     fn test_weather_emoji_one_condition_lowercase() {
-        assert_eq!(weather_to_emoji("гроза").as_str(), "⛈️");
-        assert_eq!(weather_to_emoji("дождь").as_str(), "🌧️");
-        assert_eq!(weather_to_emoji("снег").as_str(), "❄️");
-        assert_eq!(weather_to_emoji("слякоть").as_str(), "🌨️");
-        assert_eq!(weather_to_emoji("град").as_str(), "🌨️");
-        assert_eq!(weather_to_emoji("туман").as_str(), "🌫️");
-        assert_eq!(weather_to_emoji("дымка").as_str(), "🌫️");
-        assert_eq!(weather_to_emoji("ясно").as_str(), "☀️");
-        assert_eq!(weather_to_emoji("облачно").as_str(), "☁️");
-        assert_eq!(weather_to_emoji("пасмурно").as_str(), "🌥️");
+        assert_eq!(emoji("гроза").as_str(), "⛈️");
+        assert_eq!(emoji("дождь").as_str(), "🌧️");
+        assert_eq!(emoji("снег").as_str(), "❄️");
+        assert_eq!(emoji("слякоть").as_str(), "🌨️");
+        assert_eq!(emoji("град").as_str(), "🌨️");
+        assert_eq!(emoji("туман").as_str(), "🌫️");
+        assert_eq!(emoji("дымка").as_str(), "🌫️");
+        assert_eq!(emoji("ясно").as_str(), "☀️");
+        assert_eq!(emoji("облачно").as_str(), "☁️");
+        assert_eq!(emoji("пасмурно").as_str(), "🌥️");
     }
 
     #[test]
     fn test_weather_emoji_one_condition_uppercase() {
-        assert_eq!(weather_to_emoji("Гроза"), "⛈️");
-        assert_eq!(weather_to_emoji("Дождь"), "🌧️");
-        assert_eq!(weather_to_emoji("Снег"), "❄️");
-        assert_eq!(weather_to_emoji("Слякоть"), "🌨️");
-        assert_eq!(weather_to_emoji("Град"), "🌨️");
-        assert_eq!(weather_to_emoji("Туман"), "🌫️");
-        assert_eq!(weather_to_emoji("Дымка"), "🌫️");
-        assert_eq!(weather_to_emoji("Ясно"), "☀️");
-        assert_eq!(weather_to_emoji("Облачно"), "☁️");
-        assert_eq!(weather_to_emoji("Пасмурно"), "🌥️");
+        assert_eq!(emoji("Гроза"), "⛈️");
+        assert_eq!(emoji("Дождь"), "🌧️");
+        assert_eq!(emoji("Снег"), "❄️");
+        assert_eq!(emoji("Слякоть"), "🌨️");
+        assert_eq!(emoji("Град"), "🌨️");
+        assert_eq!(emoji("Туман"), "🌫️");
+        assert_eq!(emoji("Дымка"), "🌫️");
+        assert_eq!(emoji("Ясно"), "☀️");
+        assert_eq!(emoji("Облачно"), "☁️");
+        assert_eq!(emoji("Пасмурно"), "🌥️");
     }
 
     #[test]
     fn test_weather_emoji_one_condition_dot() {
-        assert_eq!(weather_to_emoji("гроза."), "⛈️");
-        assert_eq!(weather_to_emoji("дождь."), "🌧️");
-        assert_eq!(weather_to_emoji("снег."), "❄️");
-        assert_eq!(weather_to_emoji("слякоть."), "🌨️");
-        assert_eq!(weather_to_emoji("град."), "🌨️");
-        assert_eq!(weather_to_emoji("туман."), "🌫️");
-        assert_eq!(weather_to_emoji("дымка."), "🌫️");
-        assert_eq!(weather_to_emoji("ясно."), "☀️");
-        assert_eq!(weather_to_emoji("облачно."), "☁️");
-        assert_eq!(weather_to_emoji("пасмурно."), "🌥️");
+        assert_eq!(emoji("гроза."), "⛈️");
+        assert_eq!(emoji("дождь."), "🌧️");
+        assert_eq!(emoji("снег."), "❄️");
+        assert_eq!(emoji("слякоть."), "🌨️");
+        assert_eq!(emoji("град."), "🌨️");
+        assert_eq!(emoji("туман."), "🌫️");
+        assert_eq!(emoji("дымка."), "🌫️");
+        assert_eq!(emoji("ясно."), "☀️");
+        assert_eq!(emoji("облачно."), "☁️");
+        assert_eq!(emoji("пасмурно."), "🌥️");
     }
 
     #[test]
     fn test_weather_emoji_several_conditions() {
-        assert_eq!(weather_to_emoji("пасмурно, снег"), "❄️🌥️");
-        assert_eq!(weather_to_emoji("снег, пасмурно"), "❄️🌥️");
-        assert_eq!(weather_to_emoji("ясно, замерзающий дождь"), "🧊🌧️☀️");
-        assert_eq!(weather_to_emoji("переменная облачность").as_str(), "☀️☁️");
+        assert_eq!(emoji("пасмурно, снег"), "❄️🌥️");
+        assert_eq!(emoji("снег, пасмурно"), "❄️🌥️");
+        assert_eq!(emoji("ясно, замерзающий дождь"), "🧊🌧️☀️");
+        assert_eq!(emoji("переменная облачность").as_str(), "☀️☁️");
+    }
+
+    #[test]
+    fn test_weather_emoji_by_code() {
+        let config = EmojiConfig::default();
+        assert_eq!(weather_to_emoji("Ясно", Some(389), &config, "ru"), "⛈️");
+        assert_eq!(weather_to_emoji("Ясно", Some(113), &config, "ru"), "☀️");
+        assert_eq!(weather_to_emoji("Ясно", Some(1), &config, "ru"), "☀️");
+    }
+
+    #[test]
+    fn test_weather_emoji_text_order_dedup() {
+        let config = EmojiConfig {
+            order: EmojiOrder::Text,
+            dedup: true,
+            ..EmojiConfig::default()
+        };
+        assert_eq!(
+            weather_to_emoji("ясно, замерзающий дождь", None, &config, "ru"),
+            "☀️🧊🌧️"
+        );
+        assert_eq!(
+            weather_to_emoji("пасмурно, снег", None, &config, "ru"),
+            "🌥️❄️"
+        );
+        assert_eq!(weather_to_emoji("слякоть, град", None, &config, "ru"), "🌨️");
+    }
+
+    #[test]
+    fn test_weather_emoji_other_language() {
+        let config = EmojiConfig {
+            lang: "en".into(),
+            languages: HashMap::from([(
+                "en".into(),
+                EmojiMapping {
+                    codes: HashMap::new(),
+                    patterns: vec![("rain".into(), "🌧️".into()), ("sun".into(), "☀️".into())],
+                },
+            )]),
+            ..EmojiConfig::default()
+        };
+        assert_eq!(
+            weather_to_emoji("Sunny, light rain", None, &config, "en"),
+            "🌧️☀️"
+        );
+        assert_eq!(weather_to_emoji("дождь", None, &config, "ru"), "");
     }
 }
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use minijinja::context;
+
+use crate::{
+    DB, PLATFORM,
+    clock_mod::clock::Clock,
+    handlers_mod::{date_handler::date_formatter_for, lang_handler::user_lang},
+    models_mod::{button_model::ButtonModel, user_model::UserModel},
+    tools_mod::{
+        config_tools::CONFIG,
+        fair_draw_tools::{commitment, new_seed},
+        i18n_tools::tr,
+        template_tools::render,
+    },
+};
+
+/// Opens registration for the next draw and announces it in the channel with "Participate"
+/// button. Registration is closed by the draw at `draw_date_cron`.
+#[tracing::instrument]
+pub async fn handle_giveaway(user: UserModel, clock: Arc<dyn Clock>) {
+    let platform = PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone();
+    let db = DB.get().expect("Failed to get DB instance").clone();
+    let lang = user_lang(&user).await;
+
+    if user.id != CONFIG.admin {
+        platform
+            .send_message(
+                user,
+                &tr(&lang, "admin_only", "Команда доступна только админу"),
+            )
+            .await
+            .expect("Failed to send message");
+        return;
+    }
+
+    if let Some(giveaway) = db
+        .get_open_giveaway()
+        .await
+        .expect("Error accessing to database")
+    {
+        let participants = db
+            .get_giveaway_participants(giveaway.id)
+            .await
+            .expect("Error accessing to database")
+            .len();
+        platform
+            .send_message(
+                user,
+                &render(
+                    &tr(
+                        &lang,
+                        "giveaway_open_fmt",
+                        "Розыгрыш уже открыт, участников: {{ participants }}",
+                    ),
+                    context! { participants },
+                ),
+            )
+            .await
+            .expect("Failed to send message");
+        return;
+    }
+
+    // Seed is kept secret until the draw, but its hash is published now, so it can't be
+    // changed after participants are known
+    let seed = new_seed();
+    db.open_giveaway(
+        date_formatter_for(&CONFIG.default_lang).local_date(clock.now()),
+        &seed,
+    )
+    .await
+    .expect("Error accessing to database");
+
+    let announcement = render(
+        &tr(
+            &CONFIG.default_lang,
+            "giveaway_announcement_fmt",
+            "🎁 Открыта регистрация на розыгрыш! Нажмите «Участвовать» или отправьте боту /join\n\
+            Хеш сида: <code>{{ commitment }}</code>",
+        ),
+        context! { commitment => commitment(&seed) },
+    );
+    let buttons = [vec![ButtonModel::new(
+        tr(&CONFIG.default_lang, "button_join", "🎁 Участвовать"),
+        "join",
+        "",
+    )]];
+    let channel = UserModel {
+        id: CONFIG.clone().channel,
+        username: "channel".into(),
+        location: None,
+        lang: None,
+        birthday: None,
+    };
+
+    platform
+        .clone()
+        .send_message_with_buttons(channel, &announcement, &buttons)
+        .await
+        .expect("Send message failed");
+    platform
+        .send_message_with_buttons(user, &announcement, &buttons)
+        .await
+        .expect("Send message failed");
+}
+
+/// Registers user in the open giveaway via `/join` or "Participate" button.
+#[tracing::instrument]
+pub async fn handle_join(user: UserModel) {
+    let db = DB.get().expect("Failed to get DB instance").clone();
+    let lang = user_lang(&user).await;
+
+    let giveaway = db
+        .get_open_giveaway()
+        .await
+        .expect("Error accessing to database");
+    let msg = if let Some(giveaway) = giveaway {
+        let is_new = db
+            .join_giveaway(giveaway.id, &user)
+            .await
+            .expect("Error accessing to database");
+        let participants = db
+            .get_giveaway_participants(giveaway.id)
+            .await
+            .expect("Error accessing to database");
+        let is_joined = participants
+            .iter()
+            .any(|participant| participant.id == user.id);
+        let participants = participants.len();
+        let (key, fallback) = if is_new {
+            (
+                "giveaway_joined_fmt",
+                "Вы участвуете в розыгрыше! Участников: {{ participants }}",
+            )
+        } else if is_joined {
+            (
+                "giveaway_already_joined_fmt",
+                "Вы уже участвуете в розыгрыше. Участников: {{ participants }}",
+            )
+        } else {
+            // The giveaway was closed after it had been read
+            ("giveaway_closed", "Сейчас нет открытого розыгрыша")
+        };
+        render(&tr(&lang, key, fallback), context! { participants })
+    } else {
+        tr(&lang, "giveaway_closed", "Сейчас нет открытого розыгрыша")
+    };
+
+    // Users, who press the button in the channel, may have never started the bot
+    if let Err(err) = PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone()
+        .notify(user.clone(), &msg)
+        .await
+    {
+        tracing::warn!("Failed to reply to {} on join: {err}", user.id);
+    }
+}
@@ -0,0 +1,247 @@
+use std::sync::Arc;
+
+use chrono::Days;
+use minijinja::context;
+
+use crate::{
+    DB, PLATFORM,
+    clock_mod::clock::Clock,
+    handlers_mod::{
+        daily_messages_handler::handle_daily_message, date_handler::date_formatter_for,
+        draw_handler::handle_draw, weather_alerts_handler::handle_weather_alerts,
+    },
+    models_mod::{
+        job_model::{Audience, JobKind, JobModel},
+        user_model::UserModel,
+    },
+    tools_mod::{
+        config_tools::{CONFIG, Config},
+        i18n_tools::{I18N, tr},
+        template_tools::render,
+    },
+};
+
+const DEFAULT_DIGEST_FMT: &str = "Итоги за {{ days }} дн.: подписчиков {{ subscribers }}, \
+    приветствий {{ greetings }}, 👍 {{ ups }} 👎 {{ downs }}\
+    {% for draw in draws %}\nРозыгрыш {{ draw.date }}: {{ draw.username }}{% endfor %}";
+
+// Name of the channel in greetings
+const CHANNEL_USERNAME: &str = "oneprogofficial";
+
+fn recipient(id: &str, username: &str) -> UserModel {
+    UserModel {
+        id: id.into(),
+        username: username.into(),
+        location: None,
+        lang: None,
+        birthday: None,
+    }
+}
+
+/// Picks recipients of the job's audience among subscribers.
+#[tracing::instrument(skip(users, config))]
+pub fn select_audience(users: Vec<UserModel>, job: &JobModel, config: &Config) -> Vec<UserModel> {
+    match job.audience {
+        Audience::All => users
+            .into_iter()
+            .chain([recipient(&config.channel, CHANNEL_USERNAME)])
+            .collect(),
+        Audience::Users => users,
+        Audience::Channel => vec![recipient(&config.channel, CHANNEL_USERNAME)],
+        Audience::Admin => vec![recipient(&config.admin, "admin")],
+        Audience::Tag => {
+            let ids = config.tags.get(&job.tag).map_or(&[][..], Vec::as_slice);
+            users
+                .into_iter()
+                .filter(|user| ids.contains(&user.id))
+                .collect()
+        }
+    }
+}
+
+async fn audience_of(job: &JobModel) -> Vec<UserModel> {
+    let users = DB
+        .get()
+        .expect("Failed to get DB instance")
+        .get_users()
+        .await
+        .expect("Error while getting users");
+    select_audience(users, job, &CONFIG)
+}
+
+/// Sends each recipient the message rendered for their language.
+async fn send_to_all(recipients: Vec<UserModel>, render_for: impl Fn(&UserModel) -> String) {
+    let platform = PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone();
+    for user in recipients {
+        let msg = render_for(&user);
+        // One blocked user shouldn't stop the job
+        if let Err(err) = platform.clone().send_message(user.clone(), &msg).await {
+            tracing::error!("Failed to send job message to {}: {err}", user.id);
+        }
+    }
+}
+
+#[tracing::instrument]
+async fn send_digest(job: &JobModel, clock: &dyn Clock) {
+    let today = date_formatter_for(&CONFIG.default_lang).local_date(clock.now());
+    let since = today - Days::new(job.days.into());
+    let db = DB.get().expect("Failed to get DB instance").clone();
+    let digest = db
+        .get_digest(since)
+        .await
+        .expect("Error accessing to database");
+    let draws = db
+        .get_draws_since(since)
+        .await
+        .expect("Error accessing to database")
+        .into_iter()
+        .map(|draw| {
+            context! {
+                date => draw.date.format("%d.%m.%Y").to_string(),
+                username => draw
+                    .winners
+                    .iter()
+                    .map(|winner| winner.username.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let ctx = context! {
+        days => job.days,
+        subscribers => digest.subscribers,
+        greetings => digest.greetings,
+        ups => digest.ups,
+        downs => digest.downs,
+        draws,
+    };
+    send_to_all(audience_of(job).await, |user| {
+        let template = if job.template.is_empty() {
+            tr(
+                &I18N.resolve_lang(user.lang.as_deref()),
+                "digest_fmt",
+                DEFAULT_DIGEST_FMT,
+            )
+        } else {
+            job.template.clone()
+        };
+        render(&template, ctx.clone())
+    })
+    .await;
+}
+
+/// Runs scheduled job of any kind.
+#[tracing::instrument]
+pub async fn run_job(job: JobModel, clock: Arc<dyn Clock>) {
+    match job.kind {
+        JobKind::Greeting => handle_daily_message(audience_of(&job).await, &*clock).await,
+        JobKind::Draw => handle_draw(&*clock).await,
+        JobKind::WeatherAlert => handle_weather_alerts(audience_of(&job).await, &*clock).await,
+        JobKind::Message => {
+            let now = clock.now();
+            send_to_all(audience_of(&job).await, |user| {
+                let lang = I18N.resolve_lang(user.lang.as_deref());
+                render(
+                    &job.template,
+                    context! {
+                        username => user.username,
+                        date => date_formatter_for(&lang).format(now),
+                    },
+                )
+            })
+            .await;
+        }
+        JobKind::Digest => send_digest(&job, &*clock).await,
+    }
+}
+
+#[cfg(test)]
+mod jobs_tests {
+    use minijinja::context;
+
+    use crate::{
+        handlers_mod::jobs_handler::{DEFAULT_DIGEST_FMT, recipient, select_audience},
+        models_mod::{
+            job_model::{Audience, JobKind, JobModel},
+            user_model::UserModel,
+        },
+        tools_mod::{
+            config_tools::load_config,
+            template_tools::{check_template, job_template_vars, render},
+        },
+    };
+
+    fn ids(users: &[UserModel]) -> Vec<&str> {
+        users.iter().map(|user| user.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_select_audience() {
+        let config = load_config("test.toml").expect("Failed to load config");
+        let users = vec![recipient("2", "a"), recipient("3", "b")];
+        let job = |audience| JobModel {
+            tag: "vip".into(),
+            ..JobModel::builtin("", JobKind::Message, audience)
+        };
+
+        assert_eq!(
+            ids(&select_audience(
+                users.clone(),
+                &job(Audience::All),
+                &config
+            )),
+            ["2", "3", "1"]
+        );
+        assert_eq!(
+            ids(&select_audience(
+                users.clone(),
+                &job(Audience::Users),
+                &config
+            )),
+            ["2", "3"]
+        );
+        assert_eq!(
+            ids(&select_audience(
+                users.clone(),
+                &job(Audience::Channel),
+                &config
+            )),
+            ["1"]
+        );
+        assert_eq!(
+            ids(&select_audience(
+                users.clone(),
+                &job(Audience::Admin),
+                &config
+            )),
+            ["1"]
+        );
+        // Only subscribers of the tag, so "1" who isn't subscribed doesn't get messages
+        assert_eq!(
+            ids(&select_audience(users, &job(Audience::Tag), &config)),
+            ["2"]
+        );
+    }
+
+    #[test]
+    fn test_default_digest_fmt() {
+        let vars = job_template_vars(JobKind::Digest).expect("Digest has template");
+        assert!(check_template(DEFAULT_DIGEST_FMT, vars).is_ok());
+
+        let draws = vec![context! { date => "19.10.2026", username => "bob" }];
+        assert_eq!(
+            render(
+                DEFAULT_DIGEST_FMT,
+                context! {
+                    days => 7, subscribers => 10, greetings => 70, ups => 5, downs => 1, draws,
+                }
+            ),
+            "Итоги за 7 дн.: подписчиков 10, приветствий 70, 👍 5 👎 1\n\
+            Розыгрыш 19.10.2026: bob"
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{
+    DB, PLATFORM,
+    models_mod::user_model::UserModel,
+    tools_mod::{
+        i18n_tools::{I18N, tr},
+        template_tools::render,
+    },
+};
+use minijinja::context;
+
+/// Returns language chosen by user via `/lang` or, if there is none, language of user's platform.
+#[tracing::instrument]
+pub async fn user_lang(user: &UserModel) -> String {
+    let saved = DB
+        .get()
+        .expect("Failed to get DB instance")
+        .get_language(&user.id)
+        .await
+        .expect("Error accessing to database");
+    I18N.resolve_lang(saved.as_deref().or(user.lang.as_deref()))
+}
+
+#[tracing::instrument]
+pub async fn handle_lang(user: UserModel, lang: String) {
+    let platform = PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone();
+
+    // Unsupported language isn't saved, otherwise the user would silently get the default one
+    let Some(lang) = I18N.find_lang(&lang) else {
+        let current = user_lang(&user).await;
+        platform
+            .send_message(
+                user,
+                &render(
+                    &tr(
+                        &current,
+                        "lang_usage",
+                        "Укажите язык, например: /lang en. Доступны: {{ langs }}",
+                    ),
+                    context! { langs => I18N.supported_langs().join(", ") },
+                ),
+            )
+            .await
+            .expect("Failed to send message");
+        return;
+    };
+
+    DB.get()
+        .expect("Failed to get DB instance")
+        .set_language(&user.id, &lang)
+        .await
+        .expect("Error accessing to database");
+
+    platform
+        .send_message(
+            user,
+            &render(
+                &tr(&lang, "lang_fmt", "Язык изменён: {{ lang }}"),
+                context! { lang },
+            ),
+        )
+        .await
+        .expect("Failed to send message");
+}
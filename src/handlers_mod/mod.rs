@@ -1,7 +1,19 @@
+pub mod ai_report_handler;
 pub mod bind_commands_handler;
+pub mod birthday_handler;
+pub mod city_handler;
 pub mod daily_messages_handler;
 pub mod date_handler;
 pub mod draw_handler;
 pub mod formats_handler;
+pub mod giveaway_handler;
+pub mod jobs_handler;
+pub mod lang_handler;
+pub mod mychances_handler;
+pub mod rate_handler;
 pub mod scheduler_handler;
 pub mod start_handler;
+pub mod unsubscribe_handler;
+pub mod verify_draw_handler;
+pub mod weather_alerts_handler;
+pub mod weather_handler;
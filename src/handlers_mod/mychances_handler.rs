@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use minijinja::context;
+
+use crate::{
+    DB, PLATFORM,
+    clock_mod::clock::Clock,
+    handlers_mod::{
+        date_handler::date_formatter_for, draw_handler::weighted_candidates,
+        lang_handler::user_lang,
+    },
+    models_mod::user_model::UserModel,
+    tools_mod::{config_tools::CONFIG, i18n_tools::tr, template_tools::render},
+};
+
+const DEFAULT_MYCHANCES_FMT: &str = "Ваши билеты: {{ tickets }} \
+    (дней подписки: {{ days }}, оценок: {{ ratings }}, приглашённых: {{ referrals }})\
+    {% if chance %}\nШанс на главный приз: {{ chance }}% \
+    ({{ participants }} участников, {{ total_tickets }} билетов)\
+    {% else %}\nВы не участвуете в открытом розыгрыше, отправьте /join{% endif %}";
+
+/// Share of `tickets` in `total` in percent with one decimal, rounded down.
+#[tracing::instrument]
+pub fn chance_percent(tickets: u64, total: u64) -> Option<String> {
+    let permille = (tickets * 1000).checked_div(total)?;
+    Some(format!("{}.{}", permille / 10, permille % 10))
+}
+
+/// Shows user their draw tickets and chance to win the open giveaway.
+#[tracing::instrument]
+pub async fn handle_mychances(user: UserModel, clock: Arc<dyn Clock>) {
+    let db = DB.get().expect("Failed to get DB instance").clone();
+    let lang = user_lang(&user).await;
+    let today = date_formatter_for(&CONFIG.default_lang).local_date(clock.now());
+
+    let engagement = db
+        .get_engagement(&user.id)
+        .await
+        .expect("Error accessing to database");
+    let tickets = engagement.tickets(&CONFIG.draw_weights, today);
+
+    let participants = match db
+        .get_open_giveaway()
+        .await
+        .expect("Error accessing to database")
+    {
+        Some(giveaway) => db
+            .get_giveaway_participants(giveaway.id)
+            .await
+            .expect("Error accessing to database"),
+        None => vec![],
+    };
+    let candidates = weighted_candidates(&db, &participants, today).await;
+    let total_tickets = candidates.iter().map(|(_, tickets)| tickets).sum();
+    // Excluded users, e.g. recent winners, have no chance even if registered
+    let chance = candidates
+        .iter()
+        .any(|(candidate, _)| candidate.id == user.id)
+        .then(|| chance_percent(tickets, total_tickets))
+        .flatten();
+
+    PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone()
+        .send_message(
+            user,
+            &render(
+                &tr(&lang, "mychances_fmt", DEFAULT_MYCHANCES_FMT),
+                context! {
+                    tickets,
+                    days => engagement.days(today),
+                    ratings => engagement.ratings,
+                    referrals => engagement.referrals,
+                    participants => candidates.len(),
+                    total_tickets,
+                    chance,
+                },
+            ),
+        )
+        .await
+        .expect("Failed to send message");
+}
+
+#[cfg(test)]
+mod mychances_tests {
+    use minijinja::context;
+
+    use crate::{
+        handlers_mod::mychances_handler::{DEFAULT_MYCHANCES_FMT, chance_percent},
+        tools_mod::template_tools::{TEMPLATE_VARS, check_template, render},
+    };
+
+    #[test]
+    fn test_chance_percent() {
+        assert_eq!(chance_percent(1, 3), Some("33.3".into()));
+        assert_eq!(chance_percent(5, 5), Some("100.0".into()));
+        assert_eq!(chance_percent(1, 0), None);
+    }
+
+    #[test]
+    fn test_default_mychances_fmt() {
+        let (_, vars) = TEMPLATE_VARS
+            .iter()
+            .find(|(key, _)| *key == "mychances_fmt")
+            .expect("No vars of mychances_fmt");
+        assert!(check_template(DEFAULT_MYCHANCES_FMT, vars).is_ok());
+
+        assert_eq!(
+            render(
+                DEFAULT_MYCHANCES_FMT,
+                context! {
+                    tickets => 2, days => 40, ratings => 3, referrals => 0,
+                    participants => 4, total_tickets => 6, chance => "33.3",
+                }
+            ),
+            "Ваши билеты: 2 (дней подписки: 40, оценок: 3, приглашённых: 0)\n\
+            Шанс на главный приз: 33.3% (4 участников, 6 билетов)"
+        );
+        assert!(
+            render(
+                DEFAULT_MYCHANCES_FMT,
+                context! { tickets => 1, days => 0, ratings => 0, referrals => 0 }
+            )
+            .ends_with("отправьте /join")
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{
+    DB, PLATFORM, handlers_mod::lang_handler::user_lang, models_mod::user_model::UserModel,
+    tools_mod::i18n_tools::tr,
+};
+
+/// Parses `<greeting id>:up` or `<greeting id>:down` args of rating button.
+#[tracing::instrument]
+pub fn parse_rating(args: &str) -> Option<(i64, bool)> {
+    let (id, rating) = args.split_once(':')?;
+    let is_up = match rating {
+        "up" => true,
+        "down" => false,
+        _ => return None,
+    };
+    Some((id.parse().ok()?, is_up))
+}
+
+/// Handles 👍/👎 buttons under the daily greeting.
+#[tracing::instrument]
+pub async fn handle_rate(user: UserModel, args: String) {
+    let Some((greeting_id, is_up)) = parse_rating(&args) else {
+        tracing::warn!("Bad rating from {}: {args}", user.id);
+        return;
+    };
+    let lang = user_lang(&user).await;
+
+    let is_saved = DB
+        .get()
+        .expect("Failed to get DB instance")
+        .rate_greeting(greeting_id, &user.id, is_up)
+        .await
+        .expect("Error accessing to database");
+    if !is_saved {
+        tracing::warn!(
+            "{} rated greeting {greeting_id}, which isn't theirs",
+            user.id
+        );
+        return;
+    }
+
+    // Users, who press the button in the channel, may have never started the bot
+    let user_id = user.id.clone();
+    if let Err(err) = PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone()
+        .notify(user, &tr(&lang, "rate_thanks", "Спасибо за оценку!"))
+        .await
+    {
+        tracing::warn!("Failed to reply to {user_id} on rating: {err}");
+    }
+}
+
+#[cfg(test)]
+mod rate_tests {
+    use crate::handlers_mod::rate_handler::parse_rating;
+
+    #[test]
+    fn test_parse_rating() {
+        assert_eq!(parse_rating("42:up"), Some((42, true)));
+        assert_eq!(parse_rating("7:down"), Some((7, false)));
+        assert_eq!(parse_rating("up"), None);
+        assert_eq!(parse_rating("x:up"), None);
+        assert_eq!(parse_rating("1:meh"), None);
+    }
+}
@@ -1,26 +1,411 @@
-use chrono::Utc;
-use cron_tab::AsyncCron;
+use std::{str::FromStr, sync::Arc};
+
+use chrono::{DateTime, NaiveDateTime, SubsecRound, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
 
 use crate::{
-    handlers_mod::{daily_messages_handler::handle_daily_message, draw_handler::handle_draw},
-    tools_mod::config_tools::CONFIG,
+    CLOCK, DB, clock_mod::clock::Clock, handlers_mod::jobs_handler::run_job,
+    models_mod::job_model::JobModel, tools_mod::config_tools::CONFIG,
 };
 
+// Clocks never go forward by more than a day
+const MAX_GAP_MINS: i64 = 24 * 60;
+
+/// Local times of the schedule after `local`. They're evaluated without timezone, so DST
+/// doesn't skip or repeat them.
+fn local_times(
+    schedule: &Schedule,
+    local: NaiveDateTime,
+) -> impl DoubleEndedIterator<Item = NaiveDateTime> + '_ {
+    schedule
+        .after(&Utc.from_utc_datetime(&local))
+        .map(|time| time.naive_utc())
+}
+
+/// Instant of the slot at local time. Time, which repeats when clocks go back, is its first
+/// occurrence, so the job runs once. Time, which is skipped when clocks go forward, is moved to
+/// the change, so the job isn't skipped.
+fn local_slot(tz: Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    (0..=MAX_GAP_MINS)
+        .find_map(|mins| {
+            tz.from_local_datetime(&(local + TimeDelta::minutes(mins)))
+                .earliest()
+        })
+        .map(|slot| slot.with_timezone(&Utc))
+}
+
+/// The first slot of the schedule in `tz` after `after`.
+#[tracing::instrument(skip(schedule))]
+pub fn next_slot(schedule: &Schedule, tz: Tz, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let local = after.with_timezone(&tz).naive_local();
+    local_times(schedule, local).find_map(|time| local_slot(tz, time))
+}
+
+/// The latest slot of the schedule in `tz`, which isn't later than `now`.
+#[tracing::instrument(skip(schedule))]
+pub fn latest_slot(schedule: &Schedule, tz: Tz, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    // Backward iteration is strictly before the start, and slots are whole seconds
+    let local = now.with_timezone(&tz).naive_local().trunc_subsecs(0) + TimeDelta::seconds(1);
+    local_times(schedule, local)
+        .rev()
+        .find_map(|time| local_slot(tz, time))
+        .filter(|slot| *slot <= now)
+}
+
+/// The latest slot of the schedule in `tz`, which is after the last run and less than `grace`
+/// ago. Older missed slots are skipped, so the job runs at most once on startup.
+#[tracing::instrument(skip(schedule))]
+pub fn missed_slot(
+    schedule: &Schedule,
+    tz: Tz,
+    last_run: DateTime<Utc>,
+    now: DateTime<Utc>,
+    grace: TimeDelta,
+) -> Option<DateTime<Utc>> {
+    let window_start = now
+        .checked_sub_signed(grace)
+        .unwrap_or(DateTime::<Utc>::MIN_UTC);
+    latest_slot(schedule, tz, now).filter(|slot| *slot > last_run.max(window_start))
+}
+
+/// Runs the job unless it has already run for the slot, e.g. before restart.
+async fn run_slot(job: &JobModel, key: &str, slot: DateTime<Utc>, clock: &Arc<dyn Clock>) {
+    let is_claimed = DB
+        .get()
+        .expect("Failed to get DB instance")
+        .claim_job_run(key, slot)
+        .await
+        .expect("Error accessing to database");
+    if is_claimed {
+        tokio::spawn(run_job(job.clone(), clock.clone()));
+    } else {
+        tracing::info!("Job `{key}` has already run for {slot}");
+    }
+}
+
+/// Waits for each slot of the schedule in `tz` after `after` by the clock and calls `run` with it.
+#[tracing::instrument(skip(schedule, run))]
+pub async fn run_slots<F: Future<Output = ()>>(
+    schedule: &Schedule,
+    tz: Tz,
+    clock: &dyn Clock,
+    mut after: DateTime<Utc>,
+    mut run: impl FnMut(DateTime<Utc>) -> F,
+) {
+    while let Some(slot) = next_slot(schedule, tz, after) {
+        clock.sleep_until(slot).await;
+        run(slot).await;
+        after = slot;
+    }
+}
+
+/// Catches up the missed slot of the job, if any, then runs it at each slot of the schedule.
+#[tracing::instrument(skip(schedule))]
+async fn run_schedule(job: JobModel, schedule: Schedule, tz: Tz, clock: Arc<dyn Clock>) {
+    let key = job.name.clone();
+    let db = DB.get().expect("Failed to get DB instance");
+    let last_run = db
+        .get_last_run(&key)
+        .await
+        .expect("Error accessing to database");
+    let grace = TimeDelta::try_seconds(i64::try_from(CONFIG.job_grace_secs).unwrap_or(i64::MAX))
+        .unwrap_or(TimeDelta::MAX);
+    let now = clock.now();
+
+    match last_run {
+        Some(last_run) => {
+            if let Some(slot) = missed_slot(&schedule, tz, last_run, now, grace) {
+                tracing::warn!("Job `{key}` missed its run at {slot}, running it now");
+                run_slot(&job, &key, slot, &clock).await;
+            }
+        }
+        // New job hasn't missed anything, e.g. the draw shouldn't run on the first deploy
+        None => {
+            if let Some(slot) = latest_slot(&schedule, tz, now) {
+                db.claim_job_run(&key, slot)
+                    .await
+                    .expect("Error accessing to database");
+            }
+        }
+    }
+    run_slots(&schedule, tz, &*clock, now, |slot| {
+        run_slot(&job, &key, slot, &clock)
+    })
+    .await;
+}
+
 #[tracing::instrument]
 pub async fn schedule_all_tasks() {
-    let mut cron = AsyncCron::new(Utc);
+    let clock = CLOCK.get().expect("Failed to get clock instance");
+    for job in CONFIG.all_jobs() {
+        let schedule = Schedule::from_str(&job.cron)
+            .unwrap_or_else(|err| panic!("Failed to schedule job `{}`: {err}", job.cron));
+        let tz = CONFIG.job_timezone(&job);
+        tokio::spawn(run_schedule(job, schedule, tz, clock.clone()));
+    }
+}
 
-    cron.add_fn(&CONFIG.greeting_date_cron, || async {
-        handle_daily_message().await;
-    })
-    .await
-    .expect("Failed to daily");
+#[cfg(test)]
+mod scheduler_tests {
+    use std::{str::FromStr, sync::Arc, time::Duration};
 
-    cron.add_fn(&CONFIG.draw_date_cron, || async {
-        handle_draw().await;
-    })
-    .await
-    .expect("Failed to draw");
+    use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+    use chrono_tz::Tz;
+    use cron::Schedule;
+    use tokio::sync::mpsc;
+
+    use crate::{
+        clock_mod::{clock::Clock, manual_clock::ManualClock},
+        handlers_mod::scheduler_handler::{latest_slot, missed_slot, next_slot, run_slots},
+    };
+
+    fn at(day: u32, hour: u32, min: u32) -> DateTime<Utc> {
+        at_month(10, day, hour, min)
+    }
+
+    fn at_month(month: u32, day: u32, hour: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, month, day, hour, min, 0)
+            .single()
+            .expect("Invalid datetime")
+    }
+
+    fn schedule(cron: &str) -> Schedule {
+        Schedule::from_str(cron).expect("Invalid cron")
+    }
+
+    /// Slots of the schedule, which a clock moving from `from` to `to` passes.
+    fn slots(cron: &str, tz: Tz, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let schedule = schedule(cron);
+        let mut now = from;
+        let mut ans = vec![];
+        while let Some(slot) = next_slot(&schedule, tz, now).filter(|slot| *slot <= to) {
+            ans.push(slot);
+            now = slot;
+        }
+        ans
+    }
+
+    #[test]
+    fn test_next_slot_in_timezone() {
+        // 07:00 in Moscow is 04:00 UTC
+        assert_eq!(
+            slots(
+                "0 0 7 * * *",
+                Tz::Europe__Moscow,
+                at(19, 0, 0),
+                at(21, 0, 0)
+            ),
+            [at(19, 4, 0), at(20, 4, 0)]
+        );
+        assert_eq!(
+            next_slot(&schedule("0 0 7 * * *"), Tz::UTC, at(19, 7, 0)),
+            Some(at(20, 7, 0))
+        );
+    }
+
+    #[test]
+    fn test_next_slot_keeps_local_time_over_dst() {
+        // Clocks in Berlin go back on October 25 and forward on March 29
+        let slots = slots(
+            "0 0 7 * * *",
+            Tz::Europe__Berlin,
+            at(24, 0, 0),
+            at(27, 0, 0),
+        );
+        assert_eq!(slots, [at(24, 5, 0), at(25, 6, 0), at(26, 6, 0)]);
+        assert_eq!(
+            next_slot(
+                &schedule("0 0 7 * * *"),
+                Tz::Europe__Berlin,
+                at_month(3, 28, 12, 0)
+            ),
+            Some(at_month(3, 29, 5, 0))
+        );
+    }
+
+    #[test]
+    fn test_next_slot_runs_skipped_time() {
+        // 02:30 doesn't exist on March 29, the job runs at 03:00 after the change
+        assert_eq!(
+            slots(
+                "0 30 2 * * *",
+                Tz::Europe__Berlin,
+                at_month(3, 28, 12, 0),
+                at_month(3, 31, 0, 0)
+            ),
+            [at_month(3, 29, 1, 0), at_month(3, 30, 0, 30)]
+        );
+        assert_eq!(
+            slots(
+                "0 30 * * * *",
+                Tz::Europe__Berlin,
+                at_month(3, 29, 0, 0),
+                at_month(3, 29, 2, 0)
+            ),
+            [
+                at_month(3, 29, 0, 30),
+                at_month(3, 29, 1, 0),
+                at_month(3, 29, 1, 30)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_next_slot_runs_repeated_time_once() {
+        // 02:30 happens twice on October 25, the job runs at the first one
+        assert_eq!(
+            slots(
+                "0 30 2 * * *",
+                Tz::Europe__Berlin,
+                at(24, 12, 0),
+                at(27, 0, 0)
+            ),
+            [at(25, 0, 30), at(26, 1, 30)]
+        );
+        assert_eq!(
+            slots(
+                "0 30 * * * *",
+                Tz::Europe__Berlin,
+                at(24, 23, 0),
+                at(25, 3, 0)
+            ),
+            [at(24, 23, 30), at(25, 0, 30), at(25, 2, 30)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_slots_with_manual_clock() {
+        let clock = Arc::new(ManualClock::at(at(24, 12, 0)));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let runner = tokio::spawn({
+            let clock = clock.clone();
+            async move {
+                let schedule = schedule("0 0 7 * * *");
+                run_slots(
+                    &schedule,
+                    Tz::Europe__Berlin,
+                    &*clock,
+                    clock.now(),
+                    |slot| {
+                        let tx = tx.clone();
+                        async move { tx.send(slot).expect("Failed to record slot") }
+                    },
+                )
+                .await;
+            }
+        });
+        let next_run = async |rx: &mut mpsc::UnboundedReceiver<_>| {
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("Job didn't run")
+        };
+
+        // 07:00 in Berlin is 06:00 UTC after clocks go back on October 25
+        clock.set(at(25, 5, 59));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(rx.try_recv().is_err());
+        clock.advance(TimeDelta::minutes(1));
+        assert_eq!(next_run(&mut rx).await, Some(at(25, 6, 0)));
+
+        // Fast-forward over two days runs each slot once
+        clock.set(at(27, 12, 0));
+        assert_eq!(next_run(&mut rx).await, Some(at(26, 6, 0)));
+        assert_eq!(next_run(&mut rx).await, Some(at(27, 6, 0)));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(rx.try_recv().is_err());
+
+        runner.abort();
+    }
+
+    #[test]
+    fn test_latest_slot() {
+        let daily = schedule("0 0 7 * * *");
+        assert_eq!(
+            latest_slot(&daily, Tz::UTC, at(19, 7, 30)),
+            Some(at(19, 7, 0))
+        );
+        assert_eq!(
+            latest_slot(&daily, Tz::UTC, at(19, 7, 0)),
+            Some(at(19, 7, 0))
+        );
+        assert_eq!(
+            latest_slot(&daily, Tz::UTC, at(19, 6, 59)),
+            Some(at(18, 7, 0))
+        );
+    }
+
+    #[test]
+    fn test_missed_slot() {
+        let daily = schedule("0 0 7 * * *");
+        let hour = TimeDelta::hours(1);
+
+        // Down at 07:00 and back at 07:30
+        assert_eq!(
+            missed_slot(&daily, Tz::UTC, at(18, 7, 0), at(19, 7, 30), hour),
+            Some(at(19, 7, 0))
+        );
+        // Started right at the slot
+        assert_eq!(
+            missed_slot(&daily, Tz::UTC, at(18, 7, 0), at(19, 7, 0), hour),
+            Some(at(19, 7, 0))
+        );
+        // Too late to send the morning greeting
+        assert_eq!(
+            missed_slot(&daily, Tz::UTC, at(18, 7, 0), at(19, 8, 30), hour),
+            None
+        );
+        // Restart after the run at 07:00
+        assert_eq!(
+            missed_slot(&daily, Tz::UTC, at(19, 7, 0), at(19, 7, 30), hour),
+            None
+        );
+        // Restart at 06:59, the slot at 07:00 is scheduled as usual
+        assert_eq!(
+            missed_slot(&daily, Tz::UTC, at(18, 7, 0), at(19, 6, 59), hour),
+            None
+        );
+        // 07:00 in Moscow
+        assert_eq!(
+            missed_slot(
+                &daily,
+                Tz::Europe__Moscow,
+                at(18, 4, 0),
+                at(19, 4, 30),
+                hour
+            ),
+            Some(at(19, 4, 0))
+        );
+    }
 
-    cron.start().await;
+    #[test]
+    fn test_missed_slot_runs_once() {
+        let often = schedule("0 */10 * * * *");
+        // Only the latest of several missed slots runs
+        assert_eq!(
+            missed_slot(
+                &often,
+                Tz::UTC,
+                at(19, 6, 0),
+                at(19, 7, 5),
+                TimeDelta::hours(2)
+            ),
+            Some(at(19, 7, 0))
+        );
+        assert_eq!(
+            missed_slot(&often, Tz::UTC, at(1, 0, 0), at(19, 7, 5), TimeDelta::MAX),
+            Some(at(19, 7, 0))
+        );
+        // Restart during the repeated hour after the run at the first 02:30
+        assert_eq!(
+            missed_slot(
+                &schedule("0 30 2 * * *"),
+                Tz::Europe__Berlin,
+                at(25, 0, 30),
+                at(25, 1, 45),
+                TimeDelta::hours(2)
+            ),
+            None
+        );
+    }
 }
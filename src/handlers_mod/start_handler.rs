@@ -1,26 +1,47 @@
-use crate::{DB, PLATFORM, models_mod::user_model::UserModel, tools_mod::config_tools::CONFIG};
-use string_format::string_format;
+use std::sync::Arc;
+
+use crate::{
+    DB, PLATFORM,
+    clock_mod::clock::Clock,
+    handlers_mod::{date_handler::date_formatter_for, lang_handler::user_lang},
+    models_mod::user_model::UserModel,
+    tools_mod::{config_tools::CONFIG, i18n_tools::tr, template_tools::render},
+};
+use minijinja::context;
+
+/// Prefix of `/start` args in referral links, e.g. `https://t.me/<bot>?start=ref_<user id>`.
+const REFERRAL_PREFIX: &str = "ref_";
 
 #[tracing::instrument]
-pub async fn handle_start(user: UserModel) {
+pub async fn handle_start(user: UserModel, args: String, clock: Arc<dyn Clock>) {
+    let lang = user_lang(&user).await;
+
     PLATFORM
         .get()
         .expect("Failed to get platform instance")
         .clone()
         .send_message(
             user.clone(),
-            &string_format!(
-                CONFIG.start_fmt.clone(),
-                user.username.clone(),
-                user.id.clone()
+            &render(
+                &tr(&lang, "start_fmt", &CONFIG.start_fmt),
+                context! { username => user.username, id => user.id },
             ),
         )
         .await
         .expect("Failed to send message");
 
-    DB.get()
-        .expect("Failed to get DB instance")
-        .create_user(user)
+    let db = DB.get().expect("Failed to get DB instance");
+    // Referral is saved only for new users, so it's checked before the user is created
+    if let Some(referrer_id) = args.trim().strip_prefix(REFERRAL_PREFIX) {
+        let is_saved = db
+            .add_referral(&user.id, referrer_id)
+            .await
+            .expect("Error accessing to database");
+        tracing::info!("Referral of {} by {referrer_id}: {is_saved}", user.id);
+    }
+
+    let today = date_formatter_for(&CONFIG.default_lang).local_date(clock.now());
+    db.create_user(user, today)
         .await
         .expect("Error accessing to database");
 }
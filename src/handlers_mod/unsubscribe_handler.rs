@@ -0,0 +1,34 @@
+use crate::{
+    DB, PLATFORM, handlers_mod::lang_handler::user_lang, models_mod::user_model::UserModel,
+    tools_mod::i18n_tools::tr,
+};
+
+#[tracing::instrument]
+pub async fn handle_unsubscribe(user: UserModel) {
+    // Language is read before the user is deleted with it
+    let lang = user_lang(&user).await;
+
+    DB.get()
+        .expect("Failed to get DB instance")
+        .delete_user(&user.id)
+        .await
+        .expect("Error accessing to database");
+
+    let user_id = user.id.clone();
+    if let Err(err) = PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone()
+        .notify(
+            user,
+            &tr(
+                &lang,
+                "unsubscribed",
+                "Вы отписались от приветствий. Чтобы подписаться снова, отправьте /start",
+            ),
+        )
+        .await
+    {
+        tracing::warn!("Failed to reply to {user_id} on unsubscribe: {err}");
+    }
+}
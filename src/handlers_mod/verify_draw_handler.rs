@@ -0,0 +1,88 @@
+use std::{fmt::Write, fs};
+
+use crate::tools_mod::fair_draw_tools::verify_draw;
+
+const USAGE: &str = "Usage: greeting verify-draw <seed> <commitment> <participants file> [winners]";
+
+/// Parses published participants, which may be separated by commas, spaces or lines.
+/// Each is `<id>` with one ticket or `<id>:<tickets>`.
+pub fn parse_participants(text: &str) -> Result<Vec<(&str, u64)>, String> {
+    text.split(|ch: char| ch == ',' || ch.is_whitespace())
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .split_once(':')
+                .map_or(Ok((entry, 1)), |(id, tickets)| {
+                    tickets
+                        .parse()
+                        .map(|tickets| (id, tickets))
+                        .map_err(|_| format!("Bad number of tickets in `{entry}`"))
+                })
+        })
+        .collect()
+}
+
+/// Runs `verify-draw` subcommand: checks the revealed seed against the commitment, which was
+/// published when the giveaway opened, and prints winners in order of prizes.
+#[tracing::instrument]
+pub fn run_verify_draw(args: &[String]) -> Result<String, String> {
+    let [seed, commitment, path, rest @ ..] = args else {
+        return Err(USAGE.into());
+    };
+    let count = match rest {
+        [] => 1,
+        [count] => count.parse().map_err(|_| USAGE.to_string())?,
+        _ => return Err(USAGE.into()),
+    };
+    let text = fs::read_to_string(path).map_err(|err| format!("Failed to read `{path}`: {err}"))?;
+    let participants = parse_participants(&text)?;
+
+    let winners = verify_draw(seed, commitment, &participants, count)?;
+    let mut ans = format!(
+        "Seed matches the commitment, {} participants\nWinners:",
+        participants.len()
+    );
+    for (place, id) in winners.iter().enumerate() {
+        let _ = write!(ans, "\n{}. {id}", place + 1);
+    }
+    Ok(ans)
+}
+
+#[cfg(test)]
+mod verify_draw_tests {
+    use std::{env, fs};
+
+    use crate::{
+        handlers_mod::verify_draw_handler::{parse_participants, run_verify_draw},
+        tools_mod::fair_draw_tools::{commitment, fair_pick},
+    };
+
+    #[test]
+    fn test_parse_participants() {
+        assert_eq!(
+            parse_participants("1, 2:3,3\n4  5:0\n"),
+            Ok(vec![("1", 1), ("2", 3), ("3", 1), ("4", 1), ("5", 0)])
+        );
+        assert_eq!(parse_participants(" \n"), Ok(vec![]));
+        assert!(parse_participants("1:x").is_err());
+    }
+
+    #[test]
+    fn test_run_verify_draw() {
+        let path = env::temp_dir().join("greeting_verify_draw_participants.txt");
+        fs::write(&path, "3, 1, 2").expect("Failed to write participants");
+        let path = path.to_string_lossy().to_string();
+        let winners = fair_pick("seed", &[("1", 1), ("2", 1), ("3", 1)], 2);
+
+        assert_eq!(
+            run_verify_draw(&["seed".into(), commitment("seed"), path.clone(), "2".into()]),
+            Ok(format!(
+                "Seed matches the commitment, 3 participants\nWinners:\n1. {}\n2. {}",
+                winners[0], winners[1]
+            ))
+        );
+        assert!(run_verify_draw(&["other".into(), commitment("seed"), path.clone()]).is_err());
+        assert!(run_verify_draw(&["seed".into(), commitment("seed"), path, "x".into()]).is_err());
+        assert!(run_verify_draw(&["seed".into()]).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use minijinja::context;
+
+use crate::{
+    DB, PLATFORM, WEATHER,
+    clock_mod::clock::Clock,
+    handlers_mod::date_handler::date_formatter_for,
+    models_mod::{alert_model::AlertKind, user_model::UserModel, weather_model::WeatherModel},
+    tools_mod::{
+        config_tools::CONFIG,
+        i18n_tools::{I18N, tr},
+        template_tools::{render, safe},
+    },
+};
+
+// Condition codes of World Weather Online, which are used by wttr.in. Alerts are detected only by
+// codes, because status is translated to the requested language
+const STORM_CODES: [u32; 5] = [200, 386, 389, 392, 395];
+const HEAVY_SNOW_CODES: [u32; 5] = [230, 335, 338, 371, 395];
+
+#[tracing::instrument]
+pub fn detect_alerts(weather: &WeatherModel, frost_c: i32, wind_kmph: u32) -> Vec<AlertKind> {
+    let mut alerts = vec![];
+
+    if weather
+        .condition_code
+        .is_some_and(|c| STORM_CODES.contains(&c))
+    {
+        alerts.push(AlertKind::Storm);
+    }
+
+    if weather
+        .condition_code
+        .is_some_and(|c| HEAVY_SNOW_CODES.contains(&c))
+    {
+        alerts.push(AlertKind::HeavySnow);
+    }
+
+    if weather
+        .temp_c
+        .parse::<i32>()
+        .is_ok_and(|temp| temp < frost_c)
+    {
+        alerts.push(AlertKind::Frost);
+    }
+
+    if weather
+        .wind_speed_kmph
+        .parse::<u32>()
+        .is_ok_and(|wind| wind >= wind_kmph)
+    {
+        alerts.push(AlertKind::StrongWind);
+    }
+
+    alerts
+}
+
+#[tracing::instrument]
+pub fn group_by_location(users: Vec<UserModel>, default: &str) -> HashMap<String, Vec<UserModel>> {
+    let mut groups: HashMap<String, Vec<UserModel>> = HashMap::new();
+    for user in users {
+        let location = user.location.clone().unwrap_or_else(|| default.into());
+        groups.entry(location).or_default().push(user);
+    }
+    groups
+}
+
+#[tracing::instrument]
+async fn process_location(location: String, users: Vec<UserModel>, today: NaiveDate) {
+    let weather = match WEATHER
+        .get()
+        .expect("Failed to get weather instance")
+        .get_weather(&location, &CONFIG.default_lang)
+        .await
+    {
+        Ok(weather) => weather,
+        Err(err) => {
+            tracing::warn!("Failed to get weather for {location}: {err}");
+            return;
+        }
+    };
+
+    let db = DB.get().expect("Failed to get DB instance").clone();
+    let platform = PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone();
+
+    for alert in detect_alerts(&weather, CONFIG.alert_frost_c, CONFIG.alert_wind_kmph) {
+        let is_sent = db
+            .is_alert_sent(&location, alert, today)
+            .await
+            .expect("Error accessing to database");
+        if is_sent {
+            continue;
+        }
+
+        // Alert is marked as sent only after delivery, so it's retried by the next job otherwise
+        let mut is_delivered = false;
+        for user in &users {
+            let lang = I18N.resolve_lang(user.lang.as_deref());
+            let msg = render(
+                &tr(&lang, "alert_fmt", &CONFIG.alert_fmt),
+                context! {
+                    location,
+                    alert => safe(tr(
+                        &lang,
+                        &format!("alert_{}", alert.key()),
+                        alert.default_description(),
+                    )),
+                },
+            );
+            match platform.clone().send_message(user.clone(), &msg).await {
+                Ok(()) => is_delivered = true,
+                Err(err) => tracing::warn!("Failed to send alert to {}: {err}", user.id),
+            }
+        }
+        if is_delivered {
+            db.mark_alert_sent(&location, alert, today)
+                .await
+                .expect("Error accessing to database");
+        }
+    }
+}
+
+#[tracing::instrument(skip(users))]
+pub async fn handle_weather_alerts(users: Vec<UserModel>, clock: &dyn Clock) {
+    let today = date_formatter_for(&CONFIG.default_lang).local_date(clock.now());
+    for (location, users) in group_by_location(users, &CONFIG.weather_location) {
+        tokio::spawn(process_location(location, users, today));
+    }
+}
+
+#[cfg(test)]
+mod alerts_tests {
+    use crate::{
+        handlers_mod::weather_alerts_handler::{detect_alerts, group_by_location},
+        models_mod::{alert_model::AlertKind, user_model::UserModel, weather_model::WeatherModel},
+    };
+
+    fn weather(temp_c: &str, wind: &str, status: &str, code: Option<u32>) -> WeatherModel {
+        WeatherModel {
+            temp_c: temp_c.into(),
+            feels_like_c: temp_c.into(),
+            wind_speed_kmph: wind.into(),
+            min_temp_c: temp_c.into(),
+            max_temp_c: temp_c.into(),
+            status: status.into(),
+            condition_code: code,
+            is_stale: false,
+        }
+    }
+
+    #[test]
+    fn test_detect_alerts_calm() {
+        assert!(detect_alerts(&weather("5", "10", "Ясно", Some(113)), -20, 54).is_empty());
+    }
+
+    #[test]
+    fn test_detect_alerts_by_code() {
+        assert_eq!(
+            detect_alerts(&weather("20", "10", "", Some(389)), -20, 54),
+            vec![AlertKind::Storm]
+        );
+        assert_eq!(
+            detect_alerts(&weather("-5", "10", "", Some(338)), -20, 54),
+            vec![AlertKind::HeavySnow]
+        );
+        assert_eq!(
+            detect_alerts(&weather("-5", "10", "", Some(395)), -20, 54),
+            vec![AlertKind::Storm, AlertKind::HeavySnow]
+        );
+    }
+
+    #[test]
+    fn test_detect_alerts_ignore_status() {
+        assert!(detect_alerts(&weather("20", "10", "Гроза", None), -20, 54).is_empty());
+        assert!(detect_alerts(&weather("-5", "10", "Blizzard", None), -20, 54).is_empty());
+    }
+
+    #[test]
+    fn test_detect_alerts_thresholds() {
+        assert_eq!(
+            detect_alerts(&weather("-21", "54", "Ясно", Some(113)), -20, 54),
+            vec![AlertKind::Frost, AlertKind::StrongWind]
+        );
+        assert!(detect_alerts(&weather("-20", "53", "Ясно", Some(113)), -20, 54).is_empty());
+        assert!(detect_alerts(&weather("?", "?", "Ясно", Some(113)), -20, 54).is_empty());
+    }
+
+    #[test]
+    fn test_group_by_location() {
+        let user = |id: &str, location: Option<&str>| UserModel {
+            id: id.into(),
+            username: id.into(),
+            location: location.map(Into::into),
+            lang: None,
+            birthday: None,
+        };
+        let groups = group_by_location(
+            vec![
+                user("1", None),
+                user("2", Some("Казань")),
+                user("3", Some("Москва")),
+            ],
+            "Москва",
+        );
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["Москва"].len(), 2);
+        assert_eq!(groups["Казань"].len(), 1);
+    }
+}
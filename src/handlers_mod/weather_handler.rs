@@ -0,0 +1,51 @@
+use crate::{
+    DB, PLATFORM, WEATHER,
+    handlers_mod::{formats_handler::format_weather, lang_handler::user_lang},
+    models_mod::user_model::UserModel,
+    tools_mod::{config_tools::CONFIG, i18n_tools::tr},
+};
+
+#[tracing::instrument]
+pub async fn handle_weather(user: UserModel, city: String) {
+    let lang = user_lang(&user).await;
+    let location = if city.is_empty() {
+        DB.get()
+            .expect("Failed to get DB instance")
+            .get_location(&user.id)
+            .await
+            .expect("Error accessing to database")
+            .unwrap_or_else(|| CONFIG.weather_location.clone())
+    } else {
+        city
+    };
+
+    let msg = match WEATHER
+        .get()
+        .expect("Failed to get weather instance")
+        .get_weather(&location, &lang)
+        .await
+    {
+        Ok(weather) => format_weather(
+            &weather,
+            &tr(&lang, "weather_fmt", &CONFIG.weather_fmt),
+            &CONFIG.emoji,
+            &lang,
+        ),
+        Err(err) => {
+            tracing::warn!("Failed to get weather for {location}: {err}");
+            tr(
+                &lang,
+                "weather_unavailable_fmt",
+                &CONFIG.weather_unavailable_fmt,
+            )
+        }
+    };
+
+    PLATFORM
+        .get()
+        .expect("Failed to get platform instance")
+        .clone()
+        .send_message(user, &msg)
+        .await
+        .expect("Failed to send message");
+}
@@ -10,20 +10,31 @@
 //! Greeting Bot is a Telegram bot, which sends a message to all users in database with AI-generated text.
 //! This file is an entry point.
 
-use std::sync::{Arc, OnceLock};
+use std::{
+    env, process,
+    sync::{Arc, OnceLock},
+};
 
 use crate::{
     ai_mod::{ai::AiModule, ollama_ai::OllamaAi},
+    calendar_mod::{calendar::CalendarModule, file_calendar::FileCalendar},
+    clock_mod::{clock::Clock, system_clock::SystemClock},
     db_mod::{database::DatabaseModule, sqlite_database::SqliteDatabase},
     handlers_mod::{
         bind_commands_handler::bind_all_commands, scheduler_handler::schedule_all_tasks,
+        verify_draw_handler::run_verify_draw,
     },
     platforms_mod::{platform::PlatformModule, telegram_platform::TelegramPlatform},
+    tools_mod::{config_tools::CONFIG, i18n_tools::I18N, template_tools::validate_templates},
     traits_mod::create_traits::{Create, CreateAsync},
-    weather_mod::{weather::WeatherModule, wttr_in_weather::WttrInWeather},
+    weather_mod::{
+        cached_weather::CachedWeather, mirrored_weather::MirroredWeather, weather::WeatherModule,
+    },
 };
 
 mod ai_mod;
+mod calendar_mod;
+mod clock_mod;
 mod db_mod;
 mod handlers_mod;
 mod models_mod;
@@ -40,7 +51,7 @@ mod weather_mod;
 /// PLATFORM
 ///     .get()
 ///     .except("Failed to get platform instance")
-///     .bind("/start", |user: UserModel| Box::pin(handle_start(user)));
+///     .bind("/start", |user: UserModel, _args: String| Box::pin(handle_start(user)));
 /// ```
 /// Sending a message:
 /// ```
@@ -56,24 +67,30 @@ pub static PLATFORM: OnceLock<Arc<dyn PlatformModule>> = OnceLock::new();
 /// Database module allows you create users and get all of them.
 /// # Example
 /// ```
-/// let user = UserModel { id: "1234", username: "OneProg" };
+/// let user = UserModel {
+///     id: "1234",
+///     username: "OneProg",
+///     location: None,
+///     lang: None,
+///     birthday: None,
+/// };
 /// let db = DB.get().except("Failed to get database instance");
 ///
-/// db.create_user(user);
+/// db.create_user(user, Utc::now().date_naive());
 ///
 /// let users = db.get_users().except("Failed to get users");
 /// assert!(users.contains(user));
 /// ```
 pub static DB: OnceLock<Arc<dyn DatabaseModule>> = OnceLock::new();
 
-/// Ai module allows you generate response based on weather string.
+/// Ai module allows you generate response based on prompt.
 /// # Example
 /// ```
-/// let weather = String::from("Rain");
+/// let prompt = String::from("Write a greeting for rainy weather");
 /// let response = AI
 ///     .get()
 ///     .except("Failed to get Ai instance")
-///     .process(weather);
+///     .process(prompt);
 ///
 /// println!("Response: {response}");
 /// ```
@@ -85,20 +102,66 @@ pub static AI: OnceLock<Arc<dyn AiModule>> = OnceLock::new();
 /// let weather = WEATHER
 ///     .get()
 ///     .except("Failed to get weather instance")
-///     .get_weather();
+///     .get_weather("Moscow", "en");
 ///
 /// println!("Today's wind speed: {}km/h", weather.wind_speed_kmph);
 /// ```
 pub static WEATHER: OnceLock<Arc<dyn WeatherModule>> = OnceLock::new();
 
+/// Calendar module allows you get holidays and other occasions of the date.
+/// # Example
+/// ```
+/// let occasions = CALENDAR
+///     .get()
+///     .except("Failed to get calendar instance")
+///     .occasions(Utc::now().date_naive(), &user.id);
+///
+/// for occasion in occasions {
+///     println!("Today is {}", occasion.name);
+/// }
+/// ```
+pub static CALENDAR: OnceLock<Arc<dyn CalendarModule>> = OnceLock::new();
+
+/// Clock module tells current time and waits for scheduled one, so time can be controlled in tests.
+///
+/// It's read only where work starts, i.e. by the scheduler and bound commands, and is passed
+/// down to handlers from there.
+/// # Example
+/// ```
+/// let clock = CLOCK.get().except("Failed to get clock instance");
+/// let today = clock.now().date_naive();
+///
+/// clock.sleep_until(clock.now() + TimeDelta::hours(1)).await;
+/// ```
+pub static CLOCK: OnceLock<Arc<dyn Clock>> = OnceLock::new();
+
 // Comment to tesh cahying
 
 #[tracing::instrument]
 #[tokio::main]
 async fn main() {
+    // Subcommand for channel members, so it needs neither config nor connections
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if args.first().map(String::as_str) == Some("verify-draw") {
+        match run_verify_draw(&args[1..]) {
+            Ok(report) => println!("{report}"),
+            Err(err) => {
+                eprintln!("{err}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
     dotenvy::dotenv().ok();
     tracing_subscriber::fmt::init();
 
+    validate_templates(&CONFIG, &I18N).expect("Invalid templates in config or locales");
+
+    CLOCK
+        .set(SystemClock::new().expect("Failed to initialize clock"))
+        .expect("Failed to set clock");
+
     PLATFORM
         .set(TelegramPlatform::new().expect("Failed to initialize platform"))
         .expect("Failed to set platform");
@@ -114,9 +177,13 @@ async fn main() {
         .expect("Failed to set AI");
 
     WEATHER
-        .set(WttrInWeather::new().expect("Failed to initialize weather"))
+        .set(CachedWeather::<MirroredWeather>::new().expect("Failed to initialize weather"))
         .expect("Failed to set weather");
 
+    CALENDAR
+        .set(FileCalendar::new().expect("Failed to initialize calendar"))
+        .expect("Failed to set calendar");
+
     bind_all_commands().await;
     schedule_all_tasks().await;
 
@@ -0,0 +1,29 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    Storm,
+    HeavySnow,
+    Frost,
+    StrongWind,
+}
+
+impl AlertKind {
+    /// Stable key used to de-duplicate alerts in database.
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Storm => "storm",
+            Self::HeavySnow => "heavy_snow",
+            Self::Frost => "frost",
+            Self::StrongWind => "strong_wind",
+        }
+    }
+
+    /// Description, which is used when locale has no `alert_<key>` message.
+    pub const fn default_description(self) -> &'static str {
+        match self {
+            Self::Storm => "⛈️ гроза",
+            Self::HeavySnow => "❄️ сильный снегопад",
+            Self::Frost => "🥶 сильный мороз",
+            Self::StrongWind => "💨 сильный ветер",
+        }
+    }
+}
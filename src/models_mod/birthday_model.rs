@@ -0,0 +1,90 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BirthdayModel {
+    pub day: u32,
+    pub month: u32,
+}
+
+impl BirthdayModel {
+    /// Parses birthday in `DD.MM` format.
+    #[tracing::instrument]
+    pub fn parse(text: &str) -> Option<Self> {
+        let (day, month) = text.trim().split_once('.')?;
+        let birthday = Self {
+            day: day.parse().ok()?,
+            month: month.parse().ok()?,
+        };
+        // Leap year, so 29.02 is valid
+        NaiveDate::from_ymd_opt(2000, birthday.month, birthday.day).map(|_| birthday)
+    }
+
+    /// Those born on 29.02 celebrate on 28.02 in non-leap years.
+    #[tracing::instrument]
+    pub fn is_on(self, date: NaiveDate) -> bool {
+        if self.day == 29 && self.month == 2 && !date.leap_year() {
+            return date.month() == 2 && date.day() == 28;
+        }
+        self.day == date.day() && self.month == date.month()
+    }
+}
+
+impl std::fmt::Display for BirthdayModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}.{:02}", self.day, self.month)
+    }
+}
+
+#[cfg(test)]
+mod birthday_tests {
+    use chrono::NaiveDate;
+
+    use crate::models_mod::birthday_model::BirthdayModel;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).expect("Failed to create date")
+    }
+
+    #[test]
+    fn test_parse_birthday() {
+        assert_eq!(
+            BirthdayModel::parse("31.12"),
+            Some(BirthdayModel { day: 31, month: 12 })
+        );
+        assert_eq!(
+            BirthdayModel::parse(" 01.02 "),
+            Some(BirthdayModel { day: 1, month: 2 })
+        );
+        assert_eq!(
+            BirthdayModel::parse("29.02"),
+            Some(BirthdayModel { day: 29, month: 2 })
+        );
+        assert_eq!(BirthdayModel::parse("31.04"), None);
+        assert_eq!(BirthdayModel::parse("12"), None);
+        assert_eq!(BirthdayModel::parse("aa.bb"), None);
+        assert_eq!(BirthdayModel::parse(""), None);
+    }
+
+    #[test]
+    fn test_birthday_is_on() {
+        let birthday = BirthdayModel { day: 15, month: 1 };
+        assert!(birthday.is_on(date(2026, 1, 15)));
+        assert!(!birthday.is_on(date(2026, 1, 16)));
+        assert!(!birthday.is_on(date(2026, 2, 15)));
+    }
+
+    #[test]
+    fn test_birthday_leap_day() {
+        let birthday = BirthdayModel { day: 29, month: 2 };
+        assert!(birthday.is_on(date(2028, 2, 29)));
+        assert!(!birthday.is_on(date(2028, 2, 28)));
+        assert!(birthday.is_on(date(2026, 2, 28)));
+        assert!(!birthday.is_on(date(2026, 3, 1)));
+    }
+
+    #[test]
+    fn test_birthday_display() {
+        assert_eq!(BirthdayModel { day: 1, month: 2 }.to_string(), "01.02");
+    }
+}
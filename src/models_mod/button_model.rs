@@ -0,0 +1,40 @@
+/// Inline button under a message, which calls handler bound to its action on press.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ButtonModel {
+    pub text: String,
+    /// Callback data in `<action>` or `<action>:<args>` format
+    pub data: String,
+}
+
+impl ButtonModel {
+    pub fn new(text: String, action: &str, args: &str) -> Self {
+        let data = if args.is_empty() {
+            action.into()
+        } else {
+            format!("{action}:{args}")
+        };
+        Self { text, data }
+    }
+
+    /// Splits callback data into action and args.
+    pub fn parse_data(data: &str) -> (&str, &str) {
+        data.split_once(':').unwrap_or((data, ""))
+    }
+}
+
+#[cfg(test)]
+mod button_tests {
+    use crate::models_mod::button_model::ButtonModel;
+
+    #[test]
+    fn test_button_data() {
+        let button = ButtonModel::new("👍".into(), "rate", "up");
+        assert_eq!(button.data, "rate:up");
+        assert_eq!(ButtonModel::parse_data(&button.data), ("rate", "up"));
+
+        let button = ButtonModel::new("Unsubscribe".into(), "unsubscribe", "");
+        assert_eq!(button.data, "unsubscribe");
+        assert_eq!(ButtonModel::parse_data(&button.data), ("unsubscribe", ""));
+        assert_eq!(ButtonModel::parse_data("rate:a:b"), ("rate", "a:b"));
+    }
+}
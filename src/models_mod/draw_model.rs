@@ -0,0 +1,22 @@
+use chrono::NaiveDate;
+
+/// Winner of one of the draw's prizes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DrawWinnerModel {
+    /// Name of prize tier, empty if the draw has no tiers
+    pub prize: String,
+    pub user_id: String,
+    pub username: String,
+}
+
+/// Finished draw, which is kept in history.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DrawModel {
+    pub date: NaiveDate,
+    /// Winners in order of prize tiers
+    pub winners: Vec<DrawWinnerModel>,
+    pub participants: u32,
+    /// Secret seed of the giveaway, which is revealed with results, see
+    /// [`crate::tools_mod::fair_draw_tools::fair_pick`]
+    pub seed: String,
+}
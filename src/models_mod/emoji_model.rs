@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmojiOrder {
+    /// Emojis go in order of patterns in the table
+    #[default]
+    Table,
+    /// Emojis go in order of their conditions in the weather description
+    Text,
+}
+
+#[derive(Clone, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct EmojiMapping {
+    /// Provider condition code to emoji, takes precedence over patterns
+    pub codes: HashMap<String, String>,
+    /// Substring of weather description to emoji
+    pub patterns: Vec<(String, String)>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct EmojiConfig {
+    pub lang: String,
+    pub order: EmojiOrder,
+    pub dedup: bool,
+    pub languages: HashMap<String, EmojiMapping>,
+}
+
+impl Default for EmojiConfig {
+    fn default() -> Self {
+        Self {
+            lang: "ru".into(),
+            order: EmojiOrder::default(),
+            dedup: false,
+            languages: HashMap::from([("ru".into(), default_ru_mapping())]),
+        }
+    }
+}
+
+// Condition codes of World Weather Online, which are used by wttr.in
+const DEFAULT_CODES: [(&[u32], &str); 8] = [
+    (&[113], "☀️"),
+    (&[116], "⛅"),
+    (&[119], "☁️"),
+    (&[122], "🌥️"),
+    (&[143, 248, 260], "🌫️"),
+    (
+        &[176, 263, 266, 293, 296, 299, 302, 305, 308, 353, 356, 359],
+        "🌧️",
+    ),
+    (
+        &[
+            179, 182, 185, 227, 230, 281, 284, 311, 314, 317, 320, 323, 326, 329, 332, 335, 338,
+            350, 362, 365, 368, 371, 374, 377,
+        ],
+        "❄️",
+    ),
+    (&[200, 386, 389, 392, 395], "⛈️"),
+];
+
+const DEFAULT_RU_PATTERNS: [(&str, &str); 12] = [
+    ("замерзающий", "🧊"),
+    ("переменная", "☀️"),
+    ("гроза", "⛈️"),
+    ("дождь", "🌧️"),
+    ("снег", "❄️"),
+    ("слякоть", "🌨️"),
+    ("град", "🌨️"),
+    ("туман", "🌫️"),
+    ("дымка", "🌫️"),
+    ("ясно", "☀️"),
+    ("облачно", "☁️"),
+    ("пасмурно", "🌥️"),
+];
+
+fn default_ru_mapping() -> EmojiMapping {
+    EmojiMapping {
+        codes: DEFAULT_CODES
+            .iter()
+            .flat_map(|(codes, emoji)| {
+                codes
+                    .iter()
+                    .map(|code| (code.to_string(), emoji.to_string()))
+            })
+            .collect(),
+        patterns: DEFAULT_RU_PATTERNS
+            .iter()
+            .map(|(pattern, emoji)| (pattern.to_string(), emoji.to_string()))
+            .collect(),
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+/// Activity of user, which gives them extra draw tickets.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EngagementModel {
+    /// Date of subscription, `None` if user isn't subscribed
+    pub since: Option<NaiveDate>,
+    /// Number of greetings rated by user
+    pub ratings: u32,
+    /// Number of users, who subscribed by user's link
+    pub referrals: u32,
+}
+
+/// Tickets of user in the draw are `base` plus tickets for each kind of activity, which are
+/// limited by their caps. The default gives everyone one ticket, i.e. uniform draw.
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct DrawWeightsConfig {
+    pub base: u64,
+    /// One ticket per this many days of subscription, 0 to ignore
+    pub days_per_ticket: u64,
+    pub max_day_tickets: u64,
+    /// One ticket per this many rated greetings, 0 to ignore
+    pub ratings_per_ticket: u64,
+    pub max_rating_tickets: u64,
+    pub tickets_per_referral: u64,
+    pub max_referral_tickets: u64,
+}
+
+impl Default for DrawWeightsConfig {
+    fn default() -> Self {
+        Self {
+            base: 1,
+            days_per_ticket: 0,
+            max_day_tickets: 0,
+            ratings_per_ticket: 0,
+            max_rating_tickets: 0,
+            tickets_per_referral: 0,
+            max_referral_tickets: 0,
+        }
+    }
+}
+
+impl EngagementModel {
+    /// Days since subscription on `today`.
+    pub fn days(&self, today: NaiveDate) -> u64 {
+        self.since.map_or(0, |since| {
+            u64::try_from((today - since).num_days()).unwrap_or_default()
+        })
+    }
+
+    /// Draw tickets of user by the formula of `weights`.
+    #[tracing::instrument]
+    pub fn tickets(&self, weights: &DrawWeightsConfig, today: NaiveDate) -> u64 {
+        let days = self
+            .days(today)
+            .checked_div(weights.days_per_ticket)
+            .unwrap_or_default()
+            .min(weights.max_day_tickets);
+        let ratings = u64::from(self.ratings)
+            .checked_div(weights.ratings_per_ticket)
+            .unwrap_or_default()
+            .min(weights.max_rating_tickets);
+        let referrals = (u64::from(self.referrals) * weights.tickets_per_referral)
+            .min(weights.max_referral_tickets);
+        weights.base + days + ratings + referrals
+    }
+}
+
+#[cfg(test)]
+mod engagement_tests {
+    use chrono::NaiveDate;
+
+    use crate::models_mod::engagement_model::{DrawWeightsConfig, EngagementModel};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).expect("Invalid date")
+    }
+
+    #[test]
+    fn test_default_tickets() {
+        let engagement = EngagementModel {
+            since: Some(date(1)),
+            ratings: 100,
+            referrals: 10,
+        };
+        assert_eq!(
+            engagement.tickets(&DrawWeightsConfig::default(), date(19)),
+            1
+        );
+    }
+
+    #[test]
+    fn test_tickets_with_caps() {
+        let weights = DrawWeightsConfig {
+            base: 1,
+            days_per_ticket: 7,
+            max_day_tickets: 2,
+            ratings_per_ticket: 5,
+            max_rating_tickets: 10,
+            tickets_per_referral: 3,
+            max_referral_tickets: 5,
+        };
+        let engagement = EngagementModel {
+            since: Some(date(1)),
+            ratings: 12,
+            referrals: 1,
+        };
+        assert_eq!(engagement.days(date(19)), 18);
+        // 1 + 2 for 18 days + 2 for 12 ratings + 3 for referral
+        assert_eq!(engagement.tickets(&weights, date(19)), 8);
+
+        let active = EngagementModel {
+            since: Some(date(1)),
+            ratings: 100,
+            referrals: 4,
+        };
+        // Days are capped at 2, ratings at 10 and referrals at 5
+        assert_eq!(active.tickets(&weights, date(31)), 18);
+        assert_eq!(EngagementModel::default().tickets(&weights, date(19)), 1);
+    }
+}
@@ -0,0 +1,7 @@
+/// Giveaway, which is open for registration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GiveawayModel {
+    pub id: i64,
+    /// Secret seed of the draw, only its commitment is published until the draw
+    pub seed: String,
+}
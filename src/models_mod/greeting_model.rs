@@ -0,0 +1,59 @@
+use chrono::NaiveDate;
+
+/// Daily greeting with AI response, which users can rate.
+#[derive(Clone, Debug)]
+pub struct GreetingModel {
+    pub user_id: String,
+    pub text: String,
+    pub model: String,
+    pub prompt_version: String,
+    pub date: NaiveDate,
+}
+
+/// Ratings of greetings, which were generated by one model with one prompt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AiStatsModel {
+    pub model: String,
+    pub prompt_version: String,
+    pub greetings: i64,
+    pub ups: i64,
+    pub downs: i64,
+}
+
+/// Activity of the last days for digest job.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DigestModel {
+    pub subscribers: i64,
+    pub greetings: i64,
+    pub ups: i64,
+    pub downs: i64,
+}
+
+impl AiStatsModel {
+    /// Share of 👍 among all ratings, from 0 to 1.
+    pub fn average(&self) -> Option<f64> {
+        let total = self.ups + self.downs;
+        #[allow(clippy::cast_precision_loss)]
+        (total > 0).then(|| self.ups as f64 / total as f64)
+    }
+}
+
+#[cfg(test)]
+mod greeting_tests {
+    use crate::models_mod::greeting_model::AiStatsModel;
+
+    #[test]
+    fn test_average() {
+        let mut stats = AiStatsModel {
+            model: "qwen3:30b".into(),
+            prompt_version: "ba7816bf".into(),
+            greetings: 10,
+            ups: 3,
+            downs: 1,
+        };
+        assert_eq!(stats.average(), Some(0.75));
+        stats.ups = 0;
+        stats.downs = 0;
+        assert_eq!(stats.average(), None);
+    }
+}
@@ -0,0 +1,77 @@
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Daily greeting with weather and AI text
+    Greeting,
+    /// Draw of the open giveaway
+    Draw,
+    /// Message rendered from the job's template
+    Message,
+    /// Check of severe weather at users' locations
+    WeatherAlert,
+    /// Summary of subscribers, greetings, ratings and draws of the last days
+    Digest,
+}
+
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Audience {
+    /// Subscribers and the channel
+    #[default]
+    All,
+    /// Subscribers only
+    Users,
+    Channel,
+    Admin,
+    /// Users of the job's tag from `tags` of the config
+    Tag,
+}
+
+/// Scheduled job from `[[jobs]]` of the config.
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq)]
+pub struct JobModel {
+    /// Identifies runs of the job in the database, so it must be unique and stay the same when
+    /// the job is edited. Position in `jobs` if empty
+    #[serde(default)]
+    pub name: String,
+    /// When to run in cron format
+    pub cron: String,
+    /// Timezone of `cron`, `timezone` of the config if none
+    #[serde(default)]
+    pub timezone: Option<Tz>,
+    pub kind: JobKind,
+    #[serde(default)]
+    pub audience: Audience,
+    /// Tag of users for `tag` audience
+    #[serde(default)]
+    pub tag: String,
+    /// Template of `message` and `digest` jobs
+    #[serde(default)]
+    pub template: String,
+    /// Period of `digest` job in days
+    #[serde(default = "default_days")]
+    pub days: u32,
+}
+
+const fn default_days() -> u32 {
+    7
+}
+
+impl JobModel {
+    /// Job, which is configured by legacy `*_cron` field of the config.
+    pub fn builtin(cron: &str, kind: JobKind, audience: Audience) -> Self {
+        Self {
+            name: String::new(),
+            cron: cron.into(),
+            timezone: None,
+            kind,
+            audience,
+            tag: String::new(),
+            template: String::new(),
+            days: default_days(),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    #[default]
+    Photo,
+    Sticker,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MediaSource {
+    /// Local file, which is uploaded on first send
+    File(PathBuf),
+    /// Id of file, which is already stored by the platform
+    FileId(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MediaModel {
+    pub kind: MediaKind,
+    pub source: MediaSource,
+}
+
+#[derive(Clone, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct MediaConfig {
+    pub kind: MediaKind,
+    /// Directory with media files, names which aren't found there are treated as file ids
+    pub dir: String,
+    /// Provider condition code to file name or file id
+    pub codes: HashMap<String, String>,
+    /// Media for conditions without their own, may be empty to send text only
+    pub default: String,
+}
+
+impl MediaConfig {
+    /// Picks media for weather condition, if any is configured.
+    #[tracing::instrument]
+    pub fn media_for(&self, code: Option<u32>) -> Option<MediaModel> {
+        let name = code
+            .and_then(|code| self.codes.get(&code.to_string()))
+            .unwrap_or(&self.default);
+        if name.is_empty() {
+            return None;
+        }
+
+        let path = PathBuf::from(&self.dir).join(name);
+        let source = if !self.dir.is_empty() && path.is_file() {
+            MediaSource::File(path)
+        } else {
+            MediaSource::FileId(name.clone())
+        };
+
+        Some(MediaModel {
+            kind: self.kind,
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod media_tests {
+    use std::{collections::HashMap, env, fs};
+
+    use crate::models_mod::media_model::{MediaConfig, MediaKind, MediaModel, MediaSource};
+
+    #[test]
+    fn test_media_for() {
+        let dir = env::temp_dir().join(format!("greeting_media_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("Failed to create media dir");
+        fs::write(dir.join("sunny.webp"), b"RIFF").expect("Failed to write media file");
+
+        let config = MediaConfig {
+            kind: MediaKind::Sticker,
+            dir: dir.display().to_string(),
+            codes: HashMap::from([
+                ("113".into(), "sunny.webp".into()),
+                ("389".into(), "CAACAgIAAxkBAAE".into()),
+            ]),
+            default: String::new(),
+        };
+
+        assert_eq!(
+            config.media_for(Some(113)),
+            Some(MediaModel {
+                kind: MediaKind::Sticker,
+                source: MediaSource::File(dir.join("sunny.webp")),
+            })
+        );
+        assert_eq!(
+            config.media_for(Some(389)).map(|media| media.source),
+            Some(MediaSource::FileId("CAACAgIAAxkBAAE".into()))
+        );
+        assert_eq!(config.media_for(Some(116)), None);
+        assert_eq!(config.media_for(None), None);
+    }
+
+    #[test]
+    fn test_media_default() {
+        let config = MediaConfig {
+            default: "default.jpg".into(),
+            ..MediaConfig::default()
+        };
+        assert_eq!(
+            config.media_for(None),
+            Some(MediaModel {
+                kind: MediaKind::Photo,
+                source: MediaSource::FileId("default.jpg".into()),
+            })
+        );
+    }
+}
@@ -1,2 +1,14 @@
+pub mod alert_model;
+pub mod birthday_model;
+pub mod button_model;
+pub mod draw_model;
+pub mod emoji_model;
+pub mod engagement_model;
+pub mod giveaway_model;
+pub mod greeting_model;
+pub mod job_model;
+pub mod media_model;
+pub mod occasion_model;
+pub mod prize_model;
 pub mod user_model;
 pub mod weather_model;
@@ -0,0 +1,31 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::models_mod::birthday_model::BirthdayModel;
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OccasionModel {
+    pub name: String,
+    pub month: u32,
+    pub day: u32,
+    /// Yearly occasion if not set
+    pub year: Option<i32>,
+    /// Occasion of one chat only, like a channel anniversary, if set
+    pub chat: Option<String>,
+}
+
+impl OccasionModel {
+    /// Yearly occasions on 29.02 are celebrated on 28.02 in non-leap years, like birthdays.
+    #[tracing::instrument]
+    pub fn matches(&self, date: NaiveDate, chat_id: &str) -> bool {
+        let yearly = BirthdayModel {
+            day: self.day,
+            month: self.month,
+        };
+        let is_on = self.year.map_or_else(
+            || yearly.is_on(date),
+            |year| NaiveDate::from_ymd_opt(year, self.month, self.day) == Some(date),
+        );
+        is_on && self.chat.as_ref().is_none_or(|chat| chat == chat_id)
+    }
+}
@@ -0,0 +1,16 @@
+use serde::Deserialize;
+
+/// Prize tier of the draw, e.g. 1st place, which is won by `count` users.
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq)]
+pub struct PrizeModel {
+    pub name: String,
+    #[serde(default = "default_count")]
+    pub count: u32,
+    /// Message to winners of this tier, `draw_win_fmt` is used if empty
+    #[serde(default)]
+    pub win_fmt: String,
+}
+
+const fn default_count() -> u32 {
+    1
+}
@@ -1,7 +1,12 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models_mod::birthday_model::BirthdayModel;
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct UserModel {
     pub id: String,
     pub username: String,
+    pub location: Option<String>,
+    pub lang: Option<String>,
+    pub birthday: Option<BirthdayModel>,
 }
@@ -6,4 +6,6 @@ pub struct WeatherModel {
     pub min_temp_c: String,
     pub max_temp_c: String,
     pub status: String,
+    pub condition_code: Option<u32>,
+    pub is_stale: bool,
 }
@@ -0,0 +1,79 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    models_mod::{button_model::ButtonModel, media_model::MediaModel, user_model::UserModel},
+    platforms_mod::platform::{Handler, PlatformModule},
+    traits_mod::create_traits::Create,
+    types_mod::result_types::Res,
+};
+
+/// Platform for tests, which records sent messages instead of sending them.
+#[derive(Debug, Default)]
+pub struct FakePlatform {
+    sent: Mutex<Vec<(String, String)>>,
+}
+
+impl FakePlatform {
+    /// Messages, which were sent to the user, in order. Media is recorded by its caption.
+    pub fn sent_to(&self, user_id: &str) -> Vec<String> {
+        self.sent
+            .lock()
+            .expect("Fake platform is poisoned")
+            .iter()
+            .filter(|(id, _)| id == user_id)
+            .map(|(_, msg)| msg.clone())
+            .collect()
+    }
+
+    fn record(&self, user: &UserModel, msg: &str) {
+        self.sent
+            .lock()
+            .expect("Fake platform is poisoned")
+            .push((user.id.clone(), msg.into()));
+    }
+}
+
+impl Create for FakePlatform {
+    #[tracing::instrument]
+    fn new() -> Res<Arc<Self>> {
+        Ok(Arc::new(Self::default()))
+    }
+}
+
+#[async_trait::async_trait]
+impl PlatformModule for FakePlatform {
+    async fn run(self: Arc<Self>) {}
+
+    async fn send_message_with_buttons(
+        self: Arc<Self>,
+        user: UserModel,
+        msg: &str,
+        _buttons: &[Vec<ButtonModel>],
+    ) -> Res<()> {
+        self.record(&user, msg);
+        Ok(())
+    }
+
+    fn max_message_len(&self) -> usize {
+        usize::MAX
+    }
+
+    async fn send_photo(
+        self: Arc<Self>,
+        user: UserModel,
+        _photo: &MediaModel,
+        caption: &str,
+        _buttons: &[Vec<ButtonModel>],
+    ) -> Res<()> {
+        self.record(&user, caption);
+        Ok(())
+    }
+
+    async fn send_sticker(self: Arc<Self>, _user: UserModel, _sticker: &MediaModel) -> Res<()> {
+        Ok(())
+    }
+
+    async fn bind(self: Arc<Self>, _cmd: &str, _handler: Handler) {}
+
+    async fn bind_callback(self: Arc<Self>, _action: &str, _handler: Handler) {}
+}
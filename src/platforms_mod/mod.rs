@@ -1,2 +1,4 @@
+#[cfg(test)]
+pub mod fake_platform;
 pub mod platform;
 pub mod telegram_platform;
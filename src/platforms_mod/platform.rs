@@ -1,15 +1,76 @@
-use std::{fmt::Debug, pin::Pin, sync::Arc};
+use std::{error::Error, fmt::Debug, pin::Pin, sync::Arc};
 
 use crate::{
-    models_mod::user_model::UserModel, traits_mod::create_traits::Create,
+    models_mod::{
+        button_model::ButtonModel,
+        media_model::{MediaKind, MediaModel},
+        user_model::UserModel,
+    },
+    traits_mod::create_traits::Create,
     types_mod::result_types::Res,
 };
 
-pub type Handler = fn(UserModel) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+/// Command handler. Receives the user and everything after the command as arguments.
+pub type Handler = fn(UserModel, String) -> Pin<Box<dyn Future<Output = ()> + Send>>;
 
 #[async_trait::async_trait]
 pub trait PlatformModule: Send + Sync + Create + Debug {
     async fn run(self: Arc<Self>);
-    async fn send_message(self: Arc<Self>, user: UserModel, msg: &str) -> Res<()>;
+    async fn send_message(self: Arc<Self>, user: UserModel, msg: &str) -> Res<()> {
+        self.send_message_with_buttons(user, msg, &[]).await
+    }
+    /// Sends HTML message, splitting it into several ones if it's longer than [`Self::max_message_len`].
+    /// Rows of buttons are attached to the last part.
+    async fn send_message_with_buttons(
+        self: Arc<Self>,
+        user: UserModel,
+        msg: &str,
+        buttons: &[Vec<ButtonModel>],
+    ) -> Res<()>;
+    /// Short reply to user's command or button press. Reply to a press under the channel post is
+    /// shown as a notification, because the user may have never started the bot.
+    async fn notify(self: Arc<Self>, user: UserModel, msg: &str) -> Res<()> {
+        self.send_message(user, msg).await
+    }
+    /// Max length of one message, which is accepted by the platform, in units of
+    /// [`crate::tools_mod::html_tools::text_len`].
+    fn max_message_len(&self) -> usize;
+    /// Whether sending failed, because the user blocked the bot or deleted the account.
+    /// Other errors are usually temporary and shouldn't deactivate the user.
+    fn is_unreachable(&self, _err: &(dyn Error + 'static)) -> bool {
+        false
+    }
+    /// Sends photo with HTML caption, which may be empty, and rows of buttons.
+    async fn send_photo(
+        self: Arc<Self>,
+        user: UserModel,
+        photo: &MediaModel,
+        caption: &str,
+        buttons: &[Vec<ButtonModel>],
+    ) -> Res<()>;
+    async fn send_sticker(self: Arc<Self>, user: UserModel, sticker: &MediaModel) -> Res<()>;
+    /// Sends media with HTML caption and rows of buttons.
+    /// Stickers can't have captions, so it's sent as separate message.
+    async fn send_media(
+        self: Arc<Self>,
+        user: UserModel,
+        media: &MediaModel,
+        caption: &str,
+        buttons: &[Vec<ButtonModel>],
+    ) -> Res<()> {
+        match media.kind {
+            MediaKind::Photo => self.send_photo(user, media, caption, buttons).await,
+            MediaKind::Sticker => {
+                self.clone().send_sticker(user.clone(), media).await?;
+                if caption.is_empty() {
+                    Ok(())
+                } else {
+                    self.send_message_with_buttons(user, caption, buttons).await
+                }
+            }
+        }
+    }
     async fn bind(self: Arc<Self>, cmd: &str, handler: Handler);
+    /// Binds handler to presses of buttons with the action, handler receives args of the button.
+    async fn bind_callback(self: Arc<Self>, action: &str, handler: Handler);
 }
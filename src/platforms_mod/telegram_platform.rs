@@ -1,37 +1,172 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    path::PathBuf,
+    sync::Arc,
+};
 
 use teloxide::{
-    Bot,
-    payloads::SendMessageSetters,
+    ApiError, Bot, RequestError,
+    dispatching::{Dispatcher, UpdateFilterExt},
+    dptree,
+    payloads::{AnswerCallbackQuerySetters, SendMessageSetters, SendPhotoSetters},
     prelude::Requester,
-    types::{Message, ParseMode},
+    types::{
+        CallbackQuery, CallbackQueryId, FileId, InlineKeyboardButton, InlineKeyboardMarkup,
+        InputFile, Message, ParseMode, Update,
+    },
 };
 use tokio::sync::Mutex;
 
 use crate::{
-    models_mod::user_model::UserModel,
+    handlers_mod::lang_handler::user_lang,
+    models_mod::{
+        button_model::ButtonModel,
+        media_model::{MediaModel, MediaSource},
+        user_model::UserModel,
+    },
     platforms_mod::platform::{Handler, PlatformModule},
+    tools_mod::{
+        html_tools::{html_to_text, sanitize_html, split_html, text_len},
+        i18n_tools::tr,
+    },
     traits_mod::create_traits::Create,
     types_mod::result_types::Res,
 };
 
+const TELEGRAM_MAX_MESSAGE_LEN: usize = 4096;
+const TELEGRAM_MAX_CAPTION_LEN: usize = 1024;
+const TELEGRAM_MAX_CALLBACK_ANSWER_LEN: usize = 200;
+
+tokio::task_local! {
+    // Button press, which the handler in this task replies to, so concurrent presses of one user
+    // are answered each with its own reply
+    static CALLBACK_QUERY: CallbackQueryId;
+}
+
 #[derive(Debug)]
 pub struct TelegramPlatform {
     bot: Arc<Bot>,
     bindings: Mutex<HashMap<String, Handler>>,
+    callbacks: Mutex<HashMap<String, Handler>>,
+    // Ids of unanswered button presses, a press is answered either by handler or after it
+    pending_callbacks: Mutex<HashSet<CallbackQueryId>>,
+    // Ids of uploaded local files, so each file is uploaded once
+    file_ids: Mutex<HashMap<PathBuf, String>>,
 }
 
 impl TelegramPlatform {
     #[tracing::instrument]
     async fn handle_message(self: Arc<Self>, user: UserModel, msg: &str) {
-        if let Some(handler) = self.bindings.lock().await.get(msg) {
-            handler(user).await;
+        let (cmd, args) = msg.split_once(char::is_whitespace).unwrap_or((msg, ""));
+        let handler = self.bindings.lock().await.get(cmd).copied();
+        if let Some(handler) = handler {
+            handler(user, args.trim().to_string()).await;
         } else {
-            self.send_message(user, "Неизвестная команда")
+            let lang = user_lang(&user).await;
+            self.send_message(user, &tr(&lang, "unknown_command", "Неизвестная команда"))
                 .await
                 .expect("Failed to send message");
         }
     }
+
+    #[tracing::instrument]
+    async fn handle_callback(self: Arc<Self>, query: CallbackQuery) {
+        // Buttons may be pressed under channel post, so the answer goes to the user, not the chat
+        let user = UserModel {
+            id: query.from.id.to_string(),
+            username: query
+                .from
+                .username
+                .clone()
+                .unwrap_or_else(|| format!("user {}", query.from.first_name)),
+            location: None,
+            lang: query.from.language_code.clone(),
+            birthday: None,
+        };
+        let data = query.data.unwrap_or_default();
+        let (action, args) = ButtonModel::parse_data(&data);
+
+        self.pending_callbacks.lock().await.insert(query.id.clone());
+        let handler = self.callbacks.lock().await.get(action).copied();
+        if let Some(handler) = handler {
+            CALLBACK_QUERY
+                .scope(query.id.clone(), handler(user, args.to_string()))
+                .await;
+        } else {
+            tracing::warn!("Unknown callback action: {action}");
+        }
+
+        // Telegram shows progress on the button until the press is answered
+        let is_pending = self.pending_callbacks.lock().await.remove(&query.id);
+        if is_pending && let Err(err) = self.bot.answer_callback_query(query.id).await {
+            tracing::warn!("Failed to answer callback query: {err}");
+        }
+    }
+
+    fn keyboard(buttons: &[Vec<ButtonModel>]) -> Option<InlineKeyboardMarkup> {
+        (!buttons.is_empty()).then(|| {
+            InlineKeyboardMarkup::new(buttons.iter().map(|row| {
+                row.iter()
+                    .map(|button| {
+                        InlineKeyboardButton::callback(button.text.clone(), button.data.clone())
+                    })
+                    .collect::<Vec<_>>()
+            }))
+        })
+    }
+
+    /// Sends one part of a message, falling back to plain text if Telegram can't parse the markup.
+    #[tracing::instrument]
+    async fn send_part(
+        &self,
+        user: &UserModel,
+        html: String,
+        keyboard: Option<InlineKeyboardMarkup>,
+    ) -> Res<()> {
+        let mut request = self
+            .bot
+            .send_message(user.id.clone(), &html)
+            .parse_mode(ParseMode::Html);
+        if let Some(keyboard) = keyboard.clone() {
+            request = request.reply_markup(keyboard);
+        }
+        match request.await {
+            Err(RequestError::Api(ApiError::CantParseEntities(err))) => {
+                tracing::warn!("Telegram can't parse message, sending it as plain text: {err}");
+                let mut request = self.bot.send_message(user.id.clone(), html_to_text(&html));
+                if let Some(keyboard) = keyboard {
+                    request = request.reply_markup(keyboard);
+                }
+                request.await?;
+            }
+            res => {
+                res?;
+            }
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    async fn input_file(&self, source: &MediaSource) -> InputFile {
+        match source {
+            MediaSource::File(path) => self.file_ids.lock().await.get(path).map_or_else(
+                || InputFile::file(path),
+                |id| InputFile::file_id(FileId(id.clone())),
+            ),
+            MediaSource::FileId(id) => InputFile::file_id(FileId(id.clone())),
+        }
+    }
+
+    #[tracing::instrument]
+    async fn remember_file_id(&self, source: &MediaSource, id: Option<&FileId>) {
+        if let (MediaSource::File(path), Some(id)) = (source, id) {
+            self.file_ids
+                .lock()
+                .await
+                .insert(path.clone(), id.0.clone());
+        }
+    }
 }
 
 impl Create for TelegramPlatform {
@@ -41,6 +176,9 @@ impl Create for TelegramPlatform {
         let tg = Self {
             bot: Arc::new(bot),
             bindings: Mutex::new(HashMap::new()),
+            callbacks: Mutex::new(HashMap::new()),
+            pending_callbacks: Mutex::new(HashSet::new()),
+            file_ids: Mutex::new(HashMap::new()),
         };
 
         Ok(Arc::new(tg))
@@ -52,8 +190,10 @@ impl PlatformModule for TelegramPlatform {
     #[tracing::instrument]
     async fn run(self: Arc<Self>) {
         let tg = Arc::clone(&self);
+        let on_callback = Arc::clone(&self);
         let bot = tg.bot.clone();
-        teloxide::repl(bot, move |_bot: Arc<Bot>, msg: Message| {
+
+        let on_message = move |msg: Message| {
             let tg = tg.clone();
             let user = UserModel {
                 id: msg.chat.id.0.to_string(),
@@ -62,22 +202,147 @@ impl PlatformModule for TelegramPlatform {
                     .username()
                     .unwrap_or(&format!("user {}", msg.chat.first_name().unwrap_or("user")))
                     .to_string(),
+                location: None,
+                lang: msg
+                    .from
+                    .as_ref()
+                    .and_then(|from| from.language_code.clone()),
+                birthday: None,
             };
 
             async move {
                 tg.handle_message(user, msg.text().unwrap_or("")).await;
-                Ok(())
+                Ok::<_, RequestError>(())
             }
-        })
+        };
+        let on_callback = move |query: CallbackQuery| {
+            let tg = on_callback.clone();
+            async move {
+                tg.handle_callback(query).await;
+                Ok::<_, RequestError>(())
+            }
+        };
+
+        let handler = dptree::entry()
+            .branch(Update::filter_message().endpoint(on_message))
+            .branch(Update::filter_callback_query().endpoint(on_callback));
+
+        Dispatcher::builder(bot, handler)
+            .enable_ctrlc_handler()
+            .build()
+            .dispatch()
+            .await;
+    }
+
+    #[tracing::instrument]
+    async fn send_message_with_buttons(
+        self: Arc<Self>,
+        user: UserModel,
+        msg: &str,
+        buttons: &[Vec<ButtonModel>],
+    ) -> Res<()> {
+        let parts = split_html(&sanitize_html(msg), self.max_message_len());
+        let last = parts.len().saturating_sub(1);
+        for (i, part) in parts.into_iter().enumerate() {
+            let keyboard = if i == last {
+                Self::keyboard(buttons)
+            } else {
+                None
+            };
+            self.send_part(&user, part, keyboard).await?;
+        }
+        Ok(())
+    }
+
+    fn max_message_len(&self) -> usize {
+        TELEGRAM_MAX_MESSAGE_LEN
+    }
+
+    fn is_unreachable(&self, err: &(dyn Error + 'static)) -> bool {
+        matches!(
+            err.downcast_ref::<RequestError>(),
+            Some(RequestError::Api(
+                ApiError::BotBlocked | ApiError::UserDeactivated
+            ))
+        )
+    }
+
+    #[tracing::instrument]
+    async fn notify(self: Arc<Self>, user: UserModel, msg: &str) -> Res<()> {
+        let query = CALLBACK_QUERY.try_with(Clone::clone).ok();
+        let id = match query {
+            Some(id) if self.pending_callbacks.lock().await.remove(&id) => id,
+            _ => return self.send_message(user, msg).await,
+        };
+        let text = html_to_text(msg)
+            .chars()
+            .take(TELEGRAM_MAX_CALLBACK_ANSWER_LEN)
+            .collect::<String>();
+        self.bot.answer_callback_query(id).text(text).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    async fn send_photo(
+        self: Arc<Self>,
+        user: UserModel,
+        photo: &MediaModel,
+        caption: &str,
+        buttons: &[Vec<ButtonModel>],
+    ) -> Res<()> {
+        let caption = sanitize_html(caption);
+        let fits = text_len(&caption) <= TELEGRAM_MAX_CAPTION_LEN;
+        let keyboard = Self::keyboard(buttons).filter(|_| fits);
+
+        let mut request = self
+            .bot
+            .send_photo(user.id.clone(), self.input_file(&photo.source).await);
+        if fits && !caption.is_empty() {
+            request = request.caption(&caption).parse_mode(ParseMode::Html);
+        }
+        if let Some(keyboard) = keyboard.clone() {
+            request = request.reply_markup(keyboard);
+        }
+        let sent = match request.await {
+            Err(RequestError::Api(ApiError::CantParseEntities(err))) => {
+                tracing::warn!("Telegram can't parse caption, sending it as plain text: {err}");
+                let mut request = self
+                    .bot
+                    .send_photo(user.id.clone(), self.input_file(&photo.source).await)
+                    .caption(html_to_text(&caption));
+                if let Some(keyboard) = keyboard {
+                    request = request.reply_markup(keyboard);
+                }
+                request.await?
+            }
+            res => res?,
+        };
+        self.remember_file_id(
+            &photo.source,
+            sent.photo()
+                .and_then(|sizes| sizes.last())
+                .map(|size| &size.file.id),
+        )
         .await;
+
+        if !fits {
+            self.send_message_with_buttons(user, &caption, buttons)
+                .await?;
+        }
+        Ok(())
     }
 
     #[tracing::instrument]
-    async fn send_message(self: Arc<Self>, user: UserModel, msg: &str) -> Res<()> {
-        self.bot
-            .send_message(user.id, msg)
-            .parse_mode(ParseMode::Html)
+    async fn send_sticker(self: Arc<Self>, user: UserModel, sticker: &MediaModel) -> Res<()> {
+        let sent = self
+            .bot
+            .send_sticker(user.id, self.input_file(&sticker.source).await)
             .await?;
+        self.remember_file_id(
+            &sticker.source,
+            sent.sticker().map(|sticker| &sticker.file.id),
+        )
+        .await;
         Ok(())
     }
 
@@ -87,4 +352,11 @@ impl PlatformModule for TelegramPlatform {
 
         bindings.insert(cmd.to_string(), handler);
     }
+
+    #[tracing::instrument]
+    async fn bind_callback(self: Arc<Self>, action: &str, handler: Handler) {
+        let mut callbacks = self.callbacks.lock().await;
+
+        callbacks.insert(action.to_string(), handler);
+    }
 }
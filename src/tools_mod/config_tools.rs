@@ -1,29 +1,234 @@
-use std::{env, path::Path, sync::LazyLock};
+use std::{collections::HashMap, env, path::Path, sync::LazyLock};
 
+use chrono_tz::Tz;
 use config::{ConfigError, File};
 use serde::Deserialize;
 
+use crate::models_mod::{
+    emoji_model::EmojiConfig,
+    engagement_model::DrawWeightsConfig,
+    job_model::{Audience, JobKind, JobModel},
+    media_model::MediaConfig,
+    prize_model::PrizeModel,
+};
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct Config {
     pub weather_url: String,
+    /// Mirrors of `weather_url`, which are tried in order when it fails. They must serve the same
+    /// wttr.in JSON, other providers aren't supported
+    #[serde(default)]
+    pub weather_mirror_urls: Vec<String>,
     pub weather_fmt: String,
+    #[serde(default)]
+    pub weather_unavailable_fmt: String,
+    #[serde(default)]
+    pub weather_location: String,
+    /// 0 disables caching
+    #[serde(default)]
+    pub weather_cache_ttl_secs: u64,
+    /// How old cached weather may be to be shown as stale when the provider fails
+    #[serde(default = "default_weather_max_stale_secs")]
+    pub weather_max_stale_secs: u64,
     pub ai_model: String,
     pub ai_prompt: String,
+    /// `ai_prompt` with `weather_unavailable_fmt` as weather if empty
+    #[serde(default)]
+    pub ai_prompt_no_weather: String,
+    /// `ai_prompt` if empty
+    #[serde(default)]
+    pub ai_prompt_birthday: String,
     pub ai_msg_off: String,
     pub greeting_date_cron: String,
     pub greeting_fmt: String,
+    /// `greeting_fmt` if empty
+    #[serde(default)]
+    pub birthday_fmt: String,
+    #[serde(default)]
+    pub birthday_channel_fmt: String,
+    #[serde(default)]
+    pub calendar_path: String,
+    #[serde(default)]
+    pub occasions_fmt: String,
+    #[serde(default)]
+    pub ai_occasions_fmt: String,
     pub start_fmt: String,
     pub db_url: String,
     pub draw_date_cron: String,
     pub draw_win_fmt: String,
+    #[serde(default)]
+    pub draw_exclude_last: u32,
+    #[serde(default)]
+    pub draw_excluded_ids: Vec<String>,
+    /// Prize tiers in order of rank, one prize with `draw_win_fmt` if empty
+    #[serde(default)]
+    pub draw_prizes: Vec<PrizeModel>,
     pub admin: String,
     pub draw_results_fmt: String,
     pub channel: String,
+    #[serde(default = "default_city_fmt")]
+    pub city_fmt: String,
+    #[serde(default)]
+    pub alert_check_cron: String,
+    #[serde(default = "default_alert_fmt")]
+    pub alert_fmt: String,
+    #[serde(default = "default_alert_frost_c")]
+    pub alert_frost_c: i32,
+    #[serde(default = "default_alert_wind_kmph")]
+    pub alert_wind_kmph: u32,
+    #[serde(default = "default_lang")]
+    pub default_lang: String,
+    #[serde(default)]
+    pub locales_dir: String,
+    /// Parsed with the config, so a typo fails on start rather than on the first greeting
+    #[serde(default = "default_timezone")]
+    pub timezone: Tz,
+    #[serde(default)]
+    pub date_fmt: String,
+    #[serde(default)]
+    pub emoji: EmojiConfig,
+    #[serde(default)]
+    pub media: MediaConfig,
+    #[serde(default)]
+    pub draw_weights: DrawWeightsConfig,
+    /// How long after its time a job, which was missed while the bot was down, still runs
+    #[serde(default = "default_job_grace_secs")]
+    pub job_grace_secs: u64,
+    /// Scheduled jobs in addition to `greeting_date_cron`, `draw_date_cron` and `alert_check_cron`
+    #[serde(default)]
+    pub jobs: Vec<JobModel>,
+    /// Tag to ids of users for jobs with `tag` audience
+    #[serde(default)]
+    pub tags: HashMap<String, Vec<String>>,
+}
+
+// Defaults of keys, which were added after the first release, keep behaviour of older configs
+
+fn default_city_fmt() -> String {
+    "Город сохранён: {{ city }}".into()
+}
+
+fn default_alert_fmt() -> String {
+    "⚠️ {{ location }}: {{ alert }}".into()
+}
+
+const fn default_alert_frost_c() -> i32 {
+    -20
+}
+
+const fn default_alert_wind_kmph() -> u32 {
+    54
+}
+
+fn default_lang() -> String {
+    "ru".into()
+}
+
+/// Dates of older versions were always in Moscow time.
+const fn default_timezone() -> Tz {
+    Tz::Europe__Moscow
+}
+
+const fn default_weather_max_stale_secs() -> u64 {
+    86400
+}
+
+const fn default_job_grace_secs() -> u64 {
+    3600
+}
+
+impl Config {
+    /// Returns template field by its name, see [`crate::tools_mod::template_tools::TEMPLATE_VARS`].
+    pub fn template(&self, key: &str) -> Option<&str> {
+        let template = match key {
+            "weather_fmt" => &self.weather_fmt,
+            "weather_unavailable_fmt" => &self.weather_unavailable_fmt,
+            "ai_prompt" => &self.ai_prompt,
+            "ai_prompt_no_weather" => &self.ai_prompt_no_weather,
+            "ai_prompt_birthday" => &self.ai_prompt_birthday,
+            "ai_msg_off" => &self.ai_msg_off,
+            "greeting_fmt" => &self.greeting_fmt,
+            "birthday_fmt" => &self.birthday_fmt,
+            "birthday_channel_fmt" => &self.birthday_channel_fmt,
+            "occasions_fmt" => &self.occasions_fmt,
+            "ai_occasions_fmt" => &self.ai_occasions_fmt,
+            "start_fmt" => &self.start_fmt,
+            "draw_win_fmt" => &self.draw_win_fmt,
+            "draw_results_fmt" => &self.draw_results_fmt,
+            "city_fmt" => &self.city_fmt,
+            "alert_fmt" => &self.alert_fmt,
+            "date_fmt" => &self.date_fmt,
+            _ => return None,
+        };
+        Some(template)
+    }
+
+    /// Timezone of the job's cron: its own or the config's one.
+    pub fn job_timezone(&self, job: &JobModel) -> Tz {
+        job.timezone.unwrap_or(self.timezone)
+    }
+
+    /// All scheduled jobs: built-in ones of `*_cron` fields, which aren't empty and are named
+    /// after them, and `jobs`, which are named `jobs.<index>` unless they have a name.
+    pub fn all_jobs(&self) -> Vec<JobModel> {
+        let builtin = [
+            (
+                "greeting_date_cron",
+                &self.greeting_date_cron,
+                JobKind::Greeting,
+                Audience::All,
+            ),
+            (
+                "draw_date_cron",
+                &self.draw_date_cron,
+                JobKind::Draw,
+                Audience::All,
+            ),
+            (
+                "alert_check_cron",
+                &self.alert_check_cron,
+                JobKind::WeatherAlert,
+                Audience::Users,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, cron, ..)| !cron.is_empty())
+        .map(|(name, cron, kind, audience)| JobModel {
+            name: name.into(),
+            ..JobModel::builtin(cron, kind, audience)
+        });
+        let jobs = self.jobs.iter().enumerate().map(|(idx, job)| JobModel {
+            name: if job.name.is_empty() {
+                format!("jobs.{idx}")
+            } else {
+                job.name.clone()
+            },
+            ..job.clone()
+        });
+        builtin.chain(jobs).collect()
+    }
+
+    /// Prize tiers of the draw, a single unnamed prize if none are configured.
+    pub fn prizes(&self) -> Vec<PrizeModel> {
+        if self.draw_prizes.is_empty() {
+            return vec![PrizeModel {
+                name: String::new(),
+                count: 1,
+                win_fmt: String::new(),
+            }];
+        }
+        self.draw_prizes.clone()
+    }
 }
 
 pub static CONFIG: LazyLock<Config> = LazyLock::new(|| {
-    load_config(&env::var("CONFIG_PATH").expect("CONFIG_PATH must be set!"))
-        .expect("Failed to load config")
+    // Tests share the config of the repo
+    let path = if cfg!(test) {
+        "test.toml".into()
+    } else {
+        env::var("CONFIG_PATH").expect("CONFIG_PATH must be set!")
+    };
+    load_config(&path).expect("Failed to load config")
 });
 
 #[tracing::instrument]
@@ -36,13 +241,66 @@ pub fn load_config(path: &str) -> Result<Config, ConfigError> {
 
 #[cfg(test)]
 mod config_test {
-    use crate::tools_mod::config_tools::{CONFIG, load_config};
+    use std::{env, fs};
+
+    use chrono_tz::Tz;
+
+    use crate::{
+        models_mod::job_model::{Audience, JobKind, JobModel},
+        tools_mod::config_tools::{CONFIG, Config, load_config},
+    };
 
     #[test]
     fn test_load_config() {
         let path = "test.toml";
         let res = load_config(path).expect("Failed to load config");
         assert_eq!(res.start_fmt, "Hello world!");
+        assert_eq!(res.emoji.lang, "en");
+        assert!(res.emoji.dedup);
+        assert_eq!(res.emoji.languages["en"].codes["113"], "☀️");
+        assert_eq!(
+            res.emoji.languages["en"].patterns[0],
+            ("rain".into(), "🌧️".into())
+        );
+    }
+
+    #[test]
+    fn test_all_jobs() {
+        let config = load_config("test.toml").expect("Failed to load config");
+        assert_eq!(config.tags["vip"], ["1", "2"]);
+
+        // Built-in jobs with empty cron are skipped
+        let jobs = config.all_jobs();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].kind, JobKind::Message);
+        assert_eq!(jobs[0].audience, Audience::Tag);
+        assert_eq!(jobs[0].name, "jobs.0");
+        assert_eq!(jobs[1].kind, JobKind::Digest);
+        assert_eq!(jobs[1].name, "weekly_digest");
+        assert_eq!(jobs[1].days, 7);
+        assert!(jobs[1].template.is_empty());
+        assert_eq!(config.job_timezone(&jobs[0]), Tz::Europe__Moscow);
+        assert_eq!(config.job_timezone(&jobs[1]), Tz::UTC);
+
+        let config = Config {
+            greeting_date_cron: "0 0 8 * * *".into(),
+            alert_check_cron: "0 0 * * * *".into(),
+            jobs: vec![],
+            ..config
+        };
+        assert_eq!(
+            config.all_jobs(),
+            [
+                JobModel {
+                    name: "greeting_date_cron".into(),
+                    ..JobModel::builtin("0 0 8 * * *", JobKind::Greeting, Audience::All)
+                },
+                JobModel {
+                    name: "alert_check_cron".into(),
+                    ..JobModel::builtin("0 0 * * * *", JobKind::WeatherAlert, Audience::Users)
+                },
+            ]
+        );
     }
 
     #[test]
@@ -56,4 +314,45 @@ mod config_test {
     fn test_config_loading_correctly() {
         println!("{CONFIG:?}");
     }
+
+    #[test]
+    fn test_invalid_timezone() {
+        let text = fs::read_to_string("test.toml").expect("Failed to read config");
+        let path = env::temp_dir().join(format!("greeting_tz_{}.toml", std::process::id()));
+        fs::write(&path, text.replace("Europe/Moscow", "Europe/Moskva"))
+            .expect("Failed to write config");
+        assert!(load_config(&path.display().to_string()).is_err());
+    }
+
+    #[test]
+    fn test_load_old_config() {
+        // Keys of the first release, newer ones have defaults
+        let text = [
+            "weather_url",
+            "weather_fmt",
+            "ai_model",
+            "ai_prompt",
+            "ai_msg_off",
+            "greeting_date_cron",
+            "greeting_fmt",
+            "start_fmt",
+            "db_url",
+            "draw_date_cron",
+            "draw_win_fmt",
+            "admin",
+            "draw_results_fmt",
+            "channel",
+        ]
+        .map(|key| format!("{key} = \"\"\n"))
+        .concat();
+        let path = env::temp_dir().join(format!("greeting_old_{}.toml", std::process::id()));
+        fs::write(&path, text).expect("Failed to write config");
+
+        let config = load_config(&path.display().to_string()).expect("Failed to load config");
+        assert_eq!(config.timezone, Tz::Europe__Moscow);
+        assert_eq!(config.default_lang, "ru");
+        assert!(config.locales_dir.is_empty());
+        assert_eq!(config.weather_cache_ttl_secs, 0);
+        assert!(config.all_jobs().is_empty());
+    }
 }
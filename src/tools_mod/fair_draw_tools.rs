@@ -0,0 +1,161 @@
+use rand::random;
+use sha2::{Digest, Sha256};
+
+use crate::tools_mod::hash_tools::sha256_hex;
+
+/// Secret seed of a giveaway, 32 random bytes in hex.
+pub fn new_seed() -> String {
+    sha256_hex(&random::<[u8; 32]>())
+}
+
+/// Hash of the seed, which is published when the giveaway opens, so the seed can't be changed
+/// after participants are known.
+#[tracing::instrument(skip(seed))]
+pub fn commitment(seed: &str) -> String {
+    sha256_hex(seed.as_bytes())
+}
+
+/// Picks up to `count` winners, the first is the top prize. Participants are pairs of id and
+/// number of tickets. The result depends only on the seed and the set of participants, which are
+/// sorted by id, so anyone can repeat the draw: pick `k` takes ticket
+/// `u64(first 8 bytes of sha256("<seed>:<list hash>:<k>")) % remaining tickets` from the sorted
+/// participants, which are left, where list hash is sha256 of their ids joined by `\n`.
+/// With one ticket each it's the index of the winner.
+#[tracing::instrument(skip(seed, participants))]
+pub fn fair_pick(seed: &str, participants: &[(&str, u64)], count: usize) -> Vec<String> {
+    let mut remaining = participants.to_vec();
+    remaining.sort_unstable_by_key(|(id, _)| *id);
+    remaining.dedup_by_key(|(id, _)| *id);
+    remaining.retain(|(_, tickets)| *tickets > 0);
+    let ids = remaining.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+    let list_hash = sha256_hex(ids.join("\n").as_bytes());
+
+    let mut winners = vec![];
+    for pick in 0..count {
+        let total = remaining
+            .iter()
+            .fold(0_u64, |total, (_, tickets)| total.saturating_add(*tickets));
+        if total == 0 {
+            break;
+        }
+        let digest = Sha256::digest(format!("{seed}:{list_hash}:{pick}").as_bytes());
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        // Modulo bias is negligible for any real number of tickets
+        let mut ticket = u64::from_be_bytes(bytes) % total;
+        let idx = remaining
+            .iter()
+            .position(|(_, tickets)| {
+                if ticket < *tickets {
+                    return true;
+                }
+                ticket -= tickets;
+                false
+            })
+            .unwrap_or_default();
+        winners.push(remaining.remove(idx).0.into());
+    }
+    winners
+}
+
+/// Checks that the seed matches the published commitment and repeats the draw.
+pub fn verify_draw(
+    seed: &str,
+    expected_commitment: &str,
+    participants: &[(&str, u64)],
+    count: usize,
+) -> Result<Vec<String>, String> {
+    let actual = commitment(seed);
+    if !actual.eq_ignore_ascii_case(expected_commitment.trim()) {
+        return Err(format!(
+            "Seed doesn't match the commitment: sha256 of seed is {actual}"
+        ));
+    }
+    Ok(fair_pick(seed, participants, count))
+}
+
+#[cfg(test)]
+mod fair_draw_tests {
+    use crate::tools_mod::fair_draw_tools::{commitment, fair_pick, new_seed, verify_draw};
+
+    #[test]
+    fn test_new_seed() {
+        let seed = new_seed();
+        assert_eq!(seed.len(), 64);
+        assert_ne!(seed, new_seed());
+        assert_eq!(commitment(&seed).len(), 64);
+    }
+
+    #[test]
+    fn test_fair_pick_is_deterministic() {
+        let participants = [("1", 1), ("2", 1), ("3", 1), ("4", 1), ("5", 1)];
+        let winners = fair_pick("seed", &participants, 3);
+        assert_eq!(winners.len(), 3);
+        assert_eq!(winners, fair_pick("seed", &participants, 3));
+        // Order of the list doesn't matter, only the set of ids
+        assert_eq!(
+            winners,
+            fair_pick(
+                "seed",
+                &[("5", 1), ("3", 1), ("1", 1), ("4", 1), ("2", 1), ("2", 1)],
+                3
+            )
+        );
+
+        let mut unique = winners;
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), 3);
+    }
+
+    #[test]
+    fn test_fair_pick_is_stable() {
+        // Published draws must stay verifiable with future versions
+        assert_eq!(
+            fair_pick(
+                "abc",
+                &[("1", 1), ("2", 1), ("3", 1), ("4", 1), ("5", 1)],
+                5
+            ),
+            ["1", "4", "5", "3", "2"]
+        );
+        assert_eq!(
+            fair_pick(
+                "abc",
+                &[("1", 3), ("2", 1), ("3", 0), ("4", 2), ("5", 1)],
+                5
+            ),
+            ["2", "4", "1", "5"]
+        );
+    }
+
+    #[test]
+    fn test_fair_pick_weights() {
+        // Participant without tickets never wins
+        assert_eq!(fair_pick("seed", &[("1", 0), ("2", 1)], 2), ["2"]);
+
+        let mut wins = 0;
+        for seed in 0..1000 {
+            if fair_pick(&seed.to_string(), &[("1", 9), ("2", 1)], 1) == ["1"] {
+                wins += 1;
+            }
+        }
+        assert!((850..950).contains(&wins), "{wins} wins of 1000");
+    }
+
+    #[test]
+    fn test_fair_pick_few_participants() {
+        assert!(fair_pick("seed", &[], 1).is_empty());
+        assert_eq!(fair_pick("seed", &[("1", 1), ("2", 1)], 5).len(), 2);
+    }
+
+    #[test]
+    fn test_verify_draw() {
+        let hash = commitment("seed");
+        assert_eq!(
+            verify_draw("seed", &hash.to_uppercase(), &[("1", 1), ("2", 2)], 1),
+            Ok(fair_pick("seed", &[("1", 1), ("2", 2)], 1))
+        );
+        assert!(verify_draw("other", &hash, &[("1", 1)], 1).is_err());
+    }
+}
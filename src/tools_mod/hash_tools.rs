@@ -0,0 +1,33 @@
+use std::fmt::Write;
+
+use sha2::{Digest, Sha256};
+
+#[tracing::instrument(skip(data))]
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .fold(String::new(), |mut ans, byte| {
+            let _ = write!(ans, "{byte:02x}");
+            ans
+        })
+}
+
+/// Short stable id of prompt template, so ratings of different prompts can be compared.
+#[tracing::instrument]
+pub fn prompt_version(template: &str) -> String {
+    sha256_hex(template.as_bytes())[..8].into()
+}
+
+#[cfg(test)]
+mod hash_tests {
+    use crate::tools_mod::hash_tools::{prompt_version, sha256_hex};
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(prompt_version("abc"), "ba7816bf");
+    }
+}
@@ -0,0 +1,392 @@
+/// Tags, which are supported by Telegram HTML parse mode and allowed in messages.
+const ALLOWED_TAGS: [&str; 6] = ["b", "i", "a", "code", "pre", "tg-spoiler"];
+
+struct Tag<'a> {
+    name: String,
+    is_closing: bool,
+    attrs: &'a str,
+}
+
+fn parse_tag(raw: &str) -> Option<Tag<'_>> {
+    let (is_closing, raw) = raw
+        .strip_prefix('/')
+        .map_or((false, raw), |rest| (true, rest));
+    let raw = raw.trim_end_matches('/').trim_end();
+    let (name, attrs) = raw.split_once(char::is_whitespace).unwrap_or((raw, ""));
+    let name = name.to_lowercase();
+
+    if !ALLOWED_TAGS.contains(&name.as_str()) || (is_closing && !attrs.is_empty()) {
+        return None;
+    }
+
+    Some(Tag {
+        name,
+        is_closing,
+        attrs,
+    })
+}
+
+fn href(attrs: &str) -> Option<&str> {
+    let value = attrs.trim().strip_prefix("href")?.trim_start();
+    let value = value.strip_prefix('=')?.trim_start();
+    let quote = value
+        .chars()
+        .next()
+        .filter(|quote| ['"', '\''].contains(quote))?;
+    value[1..].split(quote).next()
+}
+
+/// Escapes url to be put into double quoted `href`, e.g. from the single quoted one.
+fn escape_href(url: &str) -> String {
+    let mut ans = String::with_capacity(url.len());
+    for (idx, ch) in url.char_indices() {
+        match ch {
+            '"' => ans += "&quot;",
+            '<' => ans += "&lt;",
+            '&' if !is_entity(&url[idx..]) => ans += "&amp;",
+            _ => ans.push(ch),
+        }
+    }
+    ans
+}
+
+fn is_entity(text: &str) -> bool {
+    let Some(end) = text.find(';') else {
+        return false;
+    };
+    let body = &text[1..end];
+    body.strip_prefix('#').map_or_else(
+        || ["lt", "gt", "amp", "quot"].contains(&body),
+        |num| {
+            num.strip_prefix(['x', 'X']).map_or_else(
+                || !num.is_empty() && num.chars().all(|ch| ch.is_ascii_digit()),
+                |hex| !hex.is_empty() && hex.chars().all(|ch| ch.is_ascii_hexdigit()),
+            )
+        },
+    )
+}
+
+fn push_tag(ans: &mut String, prefix: &str, name: &str) {
+    ans.push_str(prefix);
+    ans.push_str(name);
+    ans.push('>');
+}
+
+/// Repairs message for Telegram HTML parse mode: keeps only allowed tags, closes unclosed ones,
+/// drops stray closing tags and escapes all other `<`, `>` and `&`.
+#[tracing::instrument]
+pub fn sanitize_html(text: &str) -> String {
+    let mut ans = String::with_capacity(text.len());
+    let mut open: Vec<String> = vec![];
+    let mut rest = text;
+
+    while let Some(ch) = rest.chars().next() {
+        match ch {
+            '<' => {
+                let tag = rest
+                    .find('>')
+                    .and_then(|end| parse_tag(&rest[1..end]).map(|tag| (end, tag)));
+                let Some((end, tag)) = tag else {
+                    ans += "&lt;";
+                    rest = &rest[1..];
+                    continue;
+                };
+
+                if tag.is_closing {
+                    if let Some(pos) = open.iter().rposition(|name| *name == tag.name) {
+                        for name in open.drain(pos..).rev() {
+                            push_tag(&mut ans, "</", &name);
+                        }
+                    }
+                } else if tag.name == "a" {
+                    if let Some(url) = href(tag.attrs) {
+                        push_tag(&mut ans, "<a href=\"", &format!("{}\"", escape_href(url)));
+                        open.push(tag.name);
+                    }
+                } else {
+                    push_tag(&mut ans, "<", &tag.name);
+                    open.push(tag.name);
+                }
+                rest = &rest[end + 1..];
+            }
+            '>' => {
+                ans += "&gt;";
+                rest = &rest[1..];
+            }
+            '&' => {
+                ans += if is_entity(rest) { "&" } else { "&amp;" };
+                rest = &rest[1..];
+            }
+            _ => {
+                ans.push(ch);
+                rest = &rest[ch.len_utf8()..];
+            }
+        }
+    }
+
+    for name in open.into_iter().rev() {
+        push_tag(&mut ans, "</", &name);
+    }
+
+    ans
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Boundary {
+    Char,
+    Word,
+    Sentence,
+    Line,
+    Paragraph,
+}
+
+fn boundary_before(text: &str) -> Boundary {
+    if text.ends_with("\n\n") {
+        Boundary::Paragraph
+    } else if text.ends_with('\n') {
+        Boundary::Line
+    } else if [". ", "! ", "? "].iter().any(|end| text.ends_with(end)) {
+        Boundary::Sentence
+    } else if text.ends_with(' ') {
+        Boundary::Word
+    } else {
+        Boundary::Char
+    }
+}
+
+/// Length of the next tag, entity or char in bytes.
+fn token_len(html: &str) -> usize {
+    let end = match html.chars().next() {
+        Some('<') => html.find('>'),
+        Some('&') if is_entity(html) => html.find(';'),
+        _ => None,
+    };
+    end.map_or_else(
+        || html.chars().next().map_or(0, char::len_utf8),
+        |end| end + 1,
+    )
+}
+
+/// Name of sanitized tag, e.g. `a` for `<a href="...">` or `</a>`.
+fn tag_name(tag: &str) -> &str {
+    tag.trim_start_matches(['<', '/'])
+        .split([' ', '>'])
+        .next()
+        .unwrap_or_default()
+}
+
+fn closing_tags(open: &[String]) -> String {
+    let mut ans = String::new();
+    for tag in open.iter().rev() {
+        push_tag(&mut ans, "</", tag_name(tag));
+    }
+    ans
+}
+
+/// Length in UTF-16 code units, in which Telegram measures messages, e.g. emoji are mostly 2 units.
+pub fn text_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Splits sanitized HTML into parts of at most `max_len` UTF-16 code units, preferably on paragraph, line,
+/// sentence or word boundaries. Tags, which are open at the split, are closed and reopened.
+#[tracing::instrument(skip(html))]
+pub fn split_html(html: &str, max_len: usize) -> Vec<String> {
+    let mut parts = vec![];
+    // Full opening tags, e.g. `<a href="...">`, which are open at `start`
+    let mut open: Vec<String> = vec![];
+    let mut start = 0;
+
+    while start < html.len() {
+        let prefix = open.concat();
+        let mut stack = open.clone();
+        let mut len = text_len(&prefix);
+        let mut best: Option<(Boundary, usize, Vec<String>)> = None;
+        let mut pos = start;
+
+        while pos < html.len() {
+            let closing = text_len(&closing_tags(&stack));
+            if len + closing > max_len {
+                break;
+            }
+            if pos > start {
+                let boundary = boundary_before(&html[start..pos]);
+                if best.as_ref().is_none_or(|(best, ..)| boundary >= *best) {
+                    best = Some((boundary, pos, stack.clone()));
+                }
+            }
+
+            let token = &html[pos..pos + token_len(&html[pos..])];
+            if token.starts_with("</") {
+                if let Some(idx) = stack
+                    .iter()
+                    .rposition(|tag| tag_name(tag) == tag_name(token))
+                {
+                    stack.truncate(idx);
+                }
+            } else if token.starts_with('<') {
+                stack.push(token.into());
+            }
+            len += text_len(token);
+            pos += token.len();
+        }
+
+        if pos == html.len() && len + text_len(&closing_tags(&stack)) <= max_len {
+            parts.push(prefix + &html[start..]);
+            break;
+        }
+
+        // Even one token doesn't fit, so it is sent as is to move on
+        let (end, stack) = best.map_or_else(
+            || (start + token_len(&html[start..]), open.clone()),
+            |(_, end, stack)| (end, stack),
+        );
+        let text = html[start..end].trim_end();
+        if !text.trim_start().is_empty() {
+            parts.push(prefix + text + &closing_tags(&stack));
+        }
+
+        open = stack;
+        start = end + (html[end..].len() - html[end..].trim_start().len());
+    }
+
+    parts
+}
+
+/// Converts sanitized HTML to plain text for platforms or cases, where markup can't be parsed.
+#[tracing::instrument]
+pub fn html_to_text(html: &str) -> String {
+    let mut ans = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(ch) = rest.chars().next() {
+        if ch == '<'
+            && let Some(end) = rest.find('>')
+        {
+            rest = &rest[end + 1..];
+        } else if ch == '&'
+            && let Some(end) = rest.find(';')
+            && is_entity(rest)
+        {
+            ans.push(decode_entity(&rest[1..end]));
+            rest = &rest[end + 1..];
+        } else {
+            ans.push(ch);
+            rest = &rest[ch.len_utf8()..];
+        }
+    }
+
+    ans
+}
+
+fn decode_entity(body: &str) -> char {
+    match body {
+        "lt" => '<',
+        "gt" => '>',
+        "amp" => '&',
+        "quot" => '"',
+        _ => {
+            let num = &body[1..];
+            num.strip_prefix(['x', 'X'])
+                .map_or_else(|| num.parse().ok(), |hex| u32::from_str_radix(hex, 16).ok())
+                .and_then(char::from_u32)
+                .unwrap_or(char::REPLACEMENT_CHARACTER)
+        }
+    }
+}
+
+#[cfg(test)]
+mod html_tests {
+    use crate::tools_mod::html_tools::{html_to_text, sanitize_html, split_html, text_len};
+
+    #[test]
+    fn test_sanitize_keeps_allowed_tags() {
+        let html = "<b>Hi</b>, <i>x</i> <code>1</code> <pre>2</pre> <tg-spoiler>3</tg-spoiler>";
+        assert_eq!(sanitize_html(html), html);
+        assert_eq!(
+            sanitize_html("<a href=\"https://t.me\" target=\"x\">link</a>"),
+            "<a href=\"https://t.me\">link</a>"
+        );
+        assert_eq!(sanitize_html("<B>bold</B>"), "<b>bold</b>");
+    }
+
+    #[test]
+    fn test_sanitize_escapes_href() {
+        assert_eq!(
+            sanitize_html("<a href='x\"y'>link</a>"),
+            "<a href=\"x&quot;y\">link</a>"
+        );
+        assert_eq!(
+            sanitize_html("<a href='?a=1&b=2&amp;c=3'>link</a>"),
+            "<a href=\"?a=1&amp;b=2&amp;c=3\">link</a>"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_escapes_text() {
+        assert_eq!(sanitize_html("1 < 2 > 0"), "1 &lt; 2 &gt; 0");
+        assert_eq!(
+            sanitize_html("<script>x</script>"),
+            "&lt;script&gt;x&lt;/script&gt;"
+        );
+        assert_eq!(
+            sanitize_html("Tom & Jerry &amp; &#39;"),
+            "Tom &amp; Jerry &amp; &#39;"
+        );
+        assert_eq!(sanitize_html("<a>no href</a>"), "no href");
+    }
+
+    #[test]
+    fn test_sanitize_repairs_nesting() {
+        assert_eq!(sanitize_html("<b>bold <i>both"), "<b>bold <i>both</i></b>");
+        assert_eq!(sanitize_html("<b>x <i>y</b> z</i>"), "<b>x <i>y</i></b> z");
+        assert_eq!(sanitize_html("stray</b>"), "stray");
+    }
+
+    #[test]
+    fn test_split_html_short() {
+        assert_eq!(split_html("<b>Hi</b>", 10), ["<b>Hi</b>"]);
+        assert!(split_html("", 10).is_empty());
+    }
+
+    #[test]
+    fn test_split_html_boundaries() {
+        assert_eq!(
+            split_html("First para.\n\nSecond one. Third", 20),
+            ["First para.", "Second one. Third"]
+        );
+        assert_eq!(
+            split_html("One two. Three four five", 15),
+            ["One two.", "Three four five"]
+        );
+        assert_eq!(split_html("one two three", 8), ["one two", "three"]);
+        assert_eq!(split_html("abcdefgh", 3), ["abc", "def", "gh"]);
+    }
+
+    #[test]
+    fn test_split_html_reopens_tags() {
+        let parts = split_html("<b>bold text <i>here</i> and more</b>", 20);
+        assert_eq!(
+            parts,
+            ["<b>bold text</b>", "<b><i>here</i></b>", "<b>and more</b>"]
+        );
+        assert!(parts.iter().all(|part| part.chars().count() <= 20));
+        assert_eq!(split_html("a &amp; b", 6), ["a", "&amp;", "b"]);
+    }
+
+    #[test]
+    fn test_split_html_counts_utf16() {
+        // Each emoji is 2 UTF-16 code units, so only 3 of them fit into 6 units
+        let parts = split_html("😀😀😀😀😀 🎉🎉", 6);
+        assert_eq!(parts, ["😀😀😀", "😀😀", "🎉🎉"]);
+        assert!(parts.iter().all(|part| text_len(part) <= 6));
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        assert_eq!(
+            html_to_text("<b>Hi</b> &lt;3 &amp; &#x2f; &#39;"),
+            "Hi <3 & / '"
+        );
+    }
+}
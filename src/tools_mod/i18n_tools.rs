@@ -0,0 +1,163 @@
+use std::{collections::HashMap, fs, path::Path, sync::LazyLock};
+
+use config::{ConfigError, File};
+use serde::Deserialize;
+
+use crate::tools_mod::config_tools::CONFIG;
+
+/// Messages of one language, loaded from `<locales_dir>/<lang>.toml`.
+#[derive(Clone, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Catalog {
+    pub messages: HashMap<String, String>,
+}
+
+#[derive(Debug, Default)]
+pub struct I18n {
+    pub default_lang: String,
+    pub catalogs: HashMap<String, Catalog>,
+}
+
+impl I18n {
+    /// Picks a supported language for Telegram-like language code, e.g. `en-US` -> `en`.
+    #[tracing::instrument]
+    pub fn resolve_lang(&self, lang: Option<&str>) -> String {
+        lang.and_then(|lang| self.find_lang(lang))
+            .unwrap_or_else(|| self.default_lang.clone())
+    }
+
+    /// Like [`Self::resolve_lang`], but `None` if the language isn't supported.
+    #[tracing::instrument]
+    pub fn find_lang(&self, lang: &str) -> Option<String> {
+        let lang = lang
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        (self.catalogs.contains_key(&lang) || lang == self.default_lang).then_some(lang)
+    }
+
+    /// Languages, which users may choose via `/lang`, sorted.
+    #[tracing::instrument]
+    pub fn supported_langs(&self) -> Vec<String> {
+        let mut langs = self
+            .catalogs
+            .keys()
+            .chain(std::iter::once(&self.default_lang))
+            .cloned()
+            .collect::<Vec<_>>();
+        langs.sort();
+        langs.dedup();
+        langs
+    }
+
+    fn catalogs_for<'a>(&'a self, lang: &str) -> impl Iterator<Item = &'a Catalog> + use<'a> {
+        [
+            self.catalogs.get(lang),
+            self.catalogs.get(&self.default_lang),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// Looks message up in language catalog, then in default language catalog, then returns fallback.
+    #[tracing::instrument]
+    pub fn text(&self, lang: &str, key: &str, fallback: &str) -> String {
+        self.catalogs_for(lang)
+            .find_map(|catalog| catalog.messages.get(key))
+            .map_or_else(|| fallback.into(), Clone::clone)
+    }
+}
+
+pub static I18N: LazyLock<I18n> = LazyLock::new(|| {
+    load_i18n(&CONFIG.locales_dir, CONFIG.default_lang.clone()).expect("Failed to load locales")
+});
+
+#[tracing::instrument]
+pub fn load_catalog(path: &Path) -> Result<Catalog, ConfigError> {
+    config::Config::builder()
+        .add_source(File::from(path))
+        .build()?
+        .try_deserialize()
+}
+
+#[tracing::instrument]
+pub fn load_i18n(dir: &str, default_lang: String) -> Result<I18n, ConfigError> {
+    let mut catalogs = HashMap::new();
+
+    if !dir.is_empty() {
+        let entries = fs::read_dir(dir).map_err(|err| ConfigError::Foreign(Box::new(err)))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "toml")
+                && let Some(lang) = path.file_stem().and_then(|stem| stem.to_str())
+            {
+                catalogs.insert(lang.to_lowercase(), load_catalog(&path)?);
+            }
+        }
+    }
+
+    Ok(I18n {
+        default_lang,
+        catalogs,
+    })
+}
+
+/// Shortcut for [`I18n::text`] of the global instance.
+pub fn tr(lang: &str, key: &str, fallback: &str) -> String {
+    I18N.text(lang, key, fallback)
+}
+
+#[cfg(test)]
+mod i18n_tests {
+    use crate::tools_mod::i18n_tools::load_i18n;
+
+    #[test]
+    fn test_load_i18n() {
+        let i18n = load_i18n("locales", "ru".into()).expect("Failed to load locales");
+        assert!(i18n.catalogs.contains_key("ru"));
+        assert!(i18n.catalogs.contains_key("en"));
+        assert!(i18n.catalogs["en"].messages.contains_key("unknown_command"));
+    }
+
+    #[test]
+    fn test_load_i18n_bad_dir() {
+        assert!(load_i18n("bad_locales", "ru".into()).is_err());
+        let empty = load_i18n("", "ru".into()).expect("Empty dir should be allowed");
+        assert!(empty.catalogs.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_lang() {
+        let i18n = load_i18n("locales", "ru".into()).expect("Failed to load locales");
+        assert_eq!(i18n.resolve_lang(Some("en-US")), "en");
+        assert_eq!(i18n.resolve_lang(Some("EN")), "en");
+        assert_eq!(i18n.resolve_lang(Some("xx")), "ru");
+        assert_eq!(i18n.resolve_lang(None), "ru");
+        assert_eq!(i18n.find_lang("en-US"), Some("en".into()));
+        assert_eq!(i18n.find_lang("xx"), None);
+        assert_eq!(i18n.find_lang(""), None);
+    }
+
+    #[test]
+    fn test_supported_langs() {
+        let i18n = load_i18n("locales", "ru".into()).expect("Failed to load locales");
+        assert_eq!(i18n.supported_langs(), ["en", "ru"]);
+        let empty = load_i18n("", "de".into()).expect("Empty dir should be allowed");
+        assert_eq!(empty.supported_langs(), ["de"]);
+    }
+
+    #[test]
+    fn test_text_fallbacks() {
+        let i18n = load_i18n("locales", "ru".into()).expect("Failed to load locales");
+        assert_eq!(
+            i18n.text("en", "unknown_command", "fallback"),
+            "Unknown command"
+        );
+        assert_eq!(
+            i18n.text("xx", "unknown_command", "fallback"),
+            "Неизвестная команда"
+        );
+        assert_eq!(i18n.text("en", "no_such_key", "fallback"), "fallback");
+    }
+}
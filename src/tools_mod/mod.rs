@@ -1 +1,6 @@
 pub mod config_tools;
+pub mod fair_draw_tools;
+pub mod hash_tools;
+pub mod html_tools;
+pub mod i18n_tools;
+pub mod template_tools;
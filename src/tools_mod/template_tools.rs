@@ -0,0 +1,444 @@
+use std::sync::LazyLock;
+
+use minijinja::{AutoEscape, Environment, Value};
+
+use crate::{
+    models_mod::job_model::{Audience, JobKind},
+    tools_mod::{config_tools::Config, i18n_tools::I18n},
+};
+
+const GREETING_VARS: &[&str] = &["username", "date", "weather", "ai", "ai_off", "occasions"];
+
+const DIGEST_VARS: &[&str] = &["days", "subscribers", "greetings", "ups", "downs", "draws"];
+
+const DRAW_WIN_VARS: &[&str] = &["username", "prize"];
+
+const WEATHER_URL_VARS: &[&str] = &["location"];
+
+/// Variables of `date_fmt`, see [`crate::handlers_mod::date_handler::DateFormatter`].
+pub const DATE_VARS: &[&str] = &[
+    "weekday",
+    "day",
+    "day_ord",
+    "month",
+    "month_nom",
+    "month_num",
+    "year",
+    "hour",
+    "minute",
+    "time",
+];
+
+/// Variables of `template` of scheduled jobs, `None` if the job kind has no template.
+pub const fn job_template_vars(kind: JobKind) -> Option<&'static [&'static str]> {
+    match kind {
+        JobKind::Message => Some(&["username", "date"]),
+        JobKind::Digest => Some(DIGEST_VARS),
+        JobKind::Greeting | JobKind::Draw | JobKind::WeatherAlert => None,
+    }
+}
+
+/// Variables, which are passed to each template of the config and locale catalogs.
+pub const TEMPLATE_VARS: [(&str, &[&str]); 29] = [
+    (
+        "weather_fmt",
+        &[
+            "temp_c",
+            "feels_like_c",
+            "wind_speed_kmph",
+            "min_temp_c",
+            "max_temp_c",
+            "emoji",
+            "status",
+            "is_stale",
+        ],
+    ),
+    ("weather_unavailable_fmt", &[]),
+    ("ai_prompt", &["weather"]),
+    ("ai_prompt_no_weather", &[]),
+    ("ai_prompt_birthday", &["username", "weather"]),
+    ("ai_msg_off", &[]),
+    ("greeting_fmt", GREETING_VARS),
+    ("birthday_fmt", GREETING_VARS),
+    ("birthday_channel_fmt", &["usernames"]),
+    ("occasions_fmt", &["occasions"]),
+    ("ai_occasions_fmt", &["occasions"]),
+    ("start_fmt", &["username", "id"]),
+    ("draw_win_fmt", DRAW_WIN_VARS),
+    ("draw_results_fmt", &["username", "prizes", "participants"]),
+    ("city_fmt", &["city"]),
+    ("alert_fmt", &["location", "alert"]),
+    ("date_fmt", DATE_VARS),
+    ("lang_fmt", &["lang"]),
+    ("lang_usage", &["langs"]),
+    ("birthday_saved_fmt", &["birthday"]),
+    ("ai_report_fmt", &["stats"]),
+    ("draws_fmt", &["draws"]),
+    (
+        "draw_verify_fmt",
+        &["seed", "commitment", "participant_ids"],
+    ),
+    ("giveaway_announcement_fmt", &["commitment"]),
+    ("giveaway_open_fmt", &["participants"]),
+    ("giveaway_joined_fmt", &["participants"]),
+    ("giveaway_already_joined_fmt", &["participants"]),
+    ("digest_fmt", DIGEST_VARS),
+    (
+        "mychances_fmt",
+        &[
+            "tickets",
+            "days",
+            "ratings",
+            "referrals",
+            "participants",
+            "total_tickets",
+            "chance",
+        ],
+    ),
+];
+
+// Messages are sent as HTML, so variables are escaped unless marked with `safe`
+static ENV: LazyLock<Environment<'static>> = LazyLock::new(|| {
+    let mut env = Environment::new();
+    env.set_auto_escape_callback(|_| AutoEscape::Html);
+    env
+});
+
+static TEXT_ENV: LazyLock<Environment<'static>> = LazyLock::new(Environment::new);
+
+fn render_in(env: &Environment<'static>, template: &str, ctx: Value) -> String {
+    env.render_str(template, ctx).unwrap_or_else(|err| {
+        tracing::error!("Failed to render template: {err}");
+        template.into()
+    })
+}
+
+/// Renders HTML message with variables, e.g. `context! { username => "Bob" }`.
+/// Variables are escaped, so text of users, AI and weather providers can't break markup.
+/// Templates are validated on start, so on error the template itself is returned.
+#[tracing::instrument(skip(ctx))]
+pub fn render(template: &str, ctx: Value) -> String {
+    render_in(&ENV, template, ctx)
+}
+
+/// Renders plain text, e.g. AI prompt, without escaping.
+#[tracing::instrument(skip(ctx))]
+pub fn render_text(template: &str, ctx: Value) -> String {
+    render_in(&TEXT_ENV, template, ctx)
+}
+
+/// Marks trusted HTML, e.g. already rendered template or text from config, to be inserted as is.
+pub fn safe(html: String) -> Value {
+    Value::from_safe_string(html)
+}
+
+/// Checks template syntax and that it uses only `allowed` variables.
+#[tracing::instrument]
+pub fn check_template(template: &str, allowed: &[&str]) -> Result<(), String> {
+    // Old configs used positional `{}`, which is rendered as is by minijinja
+    if template.contains("{}") {
+        return Err(format!(
+            "positional `{{}}` isn't supported anymore, use named variables, e.g. `{{{{ {} }}}}`",
+            allowed.first().unwrap_or(&"username")
+        ));
+    }
+    // And old `date_fmt` used single braces, e.g. `{weekday}`
+    if let Some(var) = allowed.iter().find(|var| {
+        template
+            .match_indices(&format!("{{{var}}}"))
+            .any(|(idx, _)| !template[..idx].ends_with('{'))
+    }) {
+        return Err(format!(
+            "`{{{var}}}` isn't supported anymore, use `{{{{ {var} }}}}`"
+        ));
+    }
+
+    let compiled = ENV
+        .template_from_str(template)
+        .map_err(|err| err.to_string())?;
+
+    let mut unknown = compiled
+        .undeclared_variables(false)
+        .into_iter()
+        .filter(|var| !allowed.contains(&var.as_str()))
+        .collect::<Vec<_>>();
+
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    unknown.sort();
+    Err(format!("unknown variables: {}", unknown.join(", ")))
+}
+
+/// Checks all templates of the config and locale catalogs, errors are joined by lines.
+#[tracing::instrument(skip(config, i18n))]
+pub fn validate_templates(config: &Config, i18n: &I18n) -> Result<(), String> {
+    let mut errors = vec![];
+
+    for (key, allowed) in TEMPLATE_VARS {
+        if let Some(template) = config.template(key)
+            && let Err(err) = check_template(template, allowed)
+        {
+            errors.push(format!("`{key}` in config: {err}"));
+        }
+
+        for (lang, catalog) in &i18n.catalogs {
+            if let Some(template) = catalog.messages.get(key)
+                && let Err(err) = check_template(template, allowed)
+            {
+                errors.push(format!("`{key}` in `{lang}` catalog: {err}"));
+            }
+        }
+    }
+
+    let weather_urls = std::iter::once(&config.weather_url).chain(&config.weather_mirror_urls);
+    for url in weather_urls {
+        if let Err(err) = check_template(url, WEATHER_URL_VARS) {
+            errors.push(format!("`{url}` weather url in config: {err}"));
+        } else if !url.is_empty()
+            && !ENV
+                .template_from_str(url)
+                .is_ok_and(|compiled| compiled.undeclared_variables(false).contains("location"))
+        {
+            // Otherwise every user would get weather of the provider's guess
+            errors.push(format!(
+                "`{url}` weather url in config: `{{{{ location }}}}` is required"
+            ));
+        }
+    }
+
+    for prize in &config.draw_prizes {
+        if let Err(err) = check_template(&prize.win_fmt, DRAW_WIN_VARS) {
+            errors.push(format!(
+                "`win_fmt` of `{}` prize in config: {err}",
+                prize.name
+            ));
+        }
+    }
+
+    for (idx, job) in config.jobs.iter().enumerate() {
+        let name = format!("`template` of job #{}", idx + 1);
+        match job_template_vars(job.kind) {
+            // Empty digest template falls back to `digest_fmt`, but message has nothing to send
+            Some(_) if job.kind == JobKind::Message && job.template.is_empty() => {
+                errors.push(format!("{name} in config: is required by Message jobs"));
+            }
+            Some(allowed) => {
+                if let Err(err) = check_template(&job.template, allowed) {
+                    errors.push(format!("{name} in config: {err}"));
+                }
+            }
+            None if !job.template.is_empty() => {
+                errors.push(format!(
+                    "{name} in config: isn't used by {:?} jobs",
+                    job.kind
+                ));
+            }
+            None => {}
+        }
+        // Draw announces results to the channel and winners, so it has no audience
+        if job.kind == JobKind::Draw && (job.audience != Audience::default() || !job.tag.is_empty())
+        {
+            errors.push(format!(
+                "`audience` of job #{} in config: isn't used by Draw jobs",
+                idx + 1
+            ));
+        }
+        if job.audience == Audience::Tag && !config.tags.contains_key(&job.tag) {
+            errors.push(format!(
+                "`tag` of job #{} in config: unknown tag `{}`",
+                idx + 1,
+                job.tag
+            ));
+        }
+    }
+
+    let jobs = config.all_jobs();
+    for (idx, job) in jobs.iter().enumerate() {
+        if jobs[..idx].iter().any(|other| other.name == job.name) {
+            errors.push(format!(
+                "`name` of jobs in config: `{}` is used twice",
+                job.name
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        errors.sort();
+        Err(errors.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod template_tests {
+    use minijinja::context;
+
+    use crate::{
+        models_mod::{
+            job_model::{Audience, JobKind, JobModel},
+            prize_model::PrizeModel,
+        },
+        tools_mod::{
+            config_tools::{Config, load_config},
+            i18n_tools::load_i18n,
+            template_tools::{
+                DATE_VARS, check_template, render, render_text, safe, validate_templates,
+            },
+        },
+    };
+
+    #[test]
+    fn test_render_named() {
+        assert_eq!(
+            render(
+                "{{ weather }}, {{ username }}!",
+                context! { username => "Bob", weather => "☀️" }
+            ),
+            "☀️, Bob!"
+        );
+    }
+
+    #[test]
+    fn test_render_escapes() {
+        assert_eq!(
+            render(
+                "<b>{{ username }}</b>: {{ ai }} {{ weather }}",
+                context! { username => "<Bob>", ai => "a & b", weather => safe("<i>☀️</i>".into()) }
+            ),
+            "<b>&lt;Bob&gt;</b>: a &amp; b <i>☀️</i>"
+        );
+        assert_eq!(
+            render_text("{{ weather }}", context! { weather => "<i>☀️</i>" }),
+            "<i>☀️</i>"
+        );
+    }
+
+    #[test]
+    fn test_render_conditional() {
+        let template = "Hi{% if not ai_off %}. {{ ai }}{% endif %}";
+        assert_eq!(
+            render(template, context! { ai => "Good day", ai_off => false }),
+            "Hi. Good day"
+        );
+        assert_eq!(
+            render(template, context! { ai => "AI is off", ai_off => true }),
+            "Hi"
+        );
+    }
+
+    #[test]
+    fn test_render_bad_template() {
+        assert_eq!(render("{{ username", context! {}), "{{ username");
+    }
+
+    #[test]
+    fn test_check_template() {
+        assert!(check_template("{% if ai %}{{ ai }}{% endif %}", &["ai"]).is_ok());
+        assert_eq!(
+            check_template("{{ usrname }} {{ wether }} {{ ai }}", &["ai"]),
+            Err("unknown variables: usrname, wether".into())
+        );
+        assert!(check_template("{% if ai %}", &["ai"]).is_err());
+        assert_eq!(
+            check_template("Hi, {}!", &["username", "date"]),
+            Err("positional `{}` isn't supported anymore, use named variables, e.g. `{{ username }}`".into())
+        );
+        assert_eq!(
+            check_template("{weekday}, {{day}} {{ month }}", DATE_VARS),
+            Err("`{weekday}` isn't supported anymore, use `{{ weekday }}`".into())
+        );
+    }
+
+    #[test]
+    fn test_validate_templates() {
+        let config = load_config("test.toml").expect("Failed to load config");
+        let i18n = load_i18n("locales", "ru".into()).expect("Failed to load locales");
+        assert!(validate_templates(&config, &i18n).is_ok());
+
+        let bad = Config {
+            greeting_fmt: "{{ name }}".into(),
+            ..config.clone()
+        };
+        assert_eq!(
+            validate_templates(&bad, &i18n),
+            Err("`greeting_fmt` in config: unknown variables: name".into())
+        );
+
+        let bad_url = Config {
+            weather_url: "https://wttr.in/{}?format=j1".into(),
+            ..config.clone()
+        };
+        assert_eq!(
+            validate_templates(&bad_url, &i18n),
+            Err("`https://wttr.in/{}?format=j1` weather url in config: positional `{}` isn't supported anymore, use named variables, e.g. `{{ location }}`".into())
+        );
+
+        let no_location = Config {
+            weather_mirror_urls: vec!["https://wttr.in/?format=j1".into()],
+            ..config.clone()
+        };
+        assert_eq!(
+            validate_templates(&no_location, &i18n),
+            Err(
+                "`https://wttr.in/?format=j1` weather url in config: `{{ location }}` is required"
+                    .into()
+            )
+        );
+
+        let bad_prize = Config {
+            draw_prizes: vec![PrizeModel {
+                name: "1st".into(),
+                count: 1,
+                win_fmt: "{{ username }} won {{ prize }} {{ place }}".into(),
+            }],
+            ..config
+        };
+        assert_eq!(
+            validate_templates(&bad_prize, &i18n),
+            Err("`win_fmt` of `1st` prize in config: unknown variables: place".into())
+        );
+
+        let mut bad_jobs = bad_prize.jobs.clone();
+        bad_jobs[0].tag = "unknown".into();
+        bad_jobs[0].name = "weekly_digest".into();
+        bad_jobs[1].template = "{{ username }}".into();
+        bad_jobs.push(JobModel {
+            template: "{{ date }}".into(),
+            ..JobModel::builtin("0 0 8 * * *", JobKind::Greeting, Audience::All)
+        });
+        bad_jobs.push(JobModel::builtin(
+            "0 0 8 * * *",
+            JobKind::Message,
+            Audience::All,
+        ));
+        bad_jobs.push(JobModel::builtin(
+            "0 0 8 * * *",
+            JobKind::Draw,
+            Audience::Admin,
+        ));
+        bad_jobs.push(JobModel {
+            tag: "vip".into(),
+            ..JobModel::builtin("0 0 8 * * *", JobKind::Draw, Audience::All)
+        });
+        let bad_jobs = Config {
+            jobs: bad_jobs,
+            draw_prizes: vec![],
+            ..bad_prize
+        };
+        assert_eq!(
+            validate_templates(&bad_jobs, &i18n),
+            Err([
+                "`audience` of job #5 in config: isn't used by Draw jobs",
+                "`audience` of job #6 in config: isn't used by Draw jobs",
+                "`name` of jobs in config: `weekly_digest` is used twice",
+                "`tag` of job #1 in config: unknown tag `unknown`",
+                "`template` of job #2 in config: unknown variables: username",
+                "`template` of job #3 in config: isn't used by Greeting jobs",
+                "`template` of job #4 in config: is required by Message jobs",
+            ]
+            .join("\n"))
+        );
+    }
+}
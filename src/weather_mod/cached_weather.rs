@@ -0,0 +1,247 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::Mutex;
+
+use crate::{
+    models_mod::weather_model::WeatherModel, tools_mod::config_tools::CONFIG,
+    traits_mod::create_traits::Create, types_mod::result_types::Res,
+    weather_mod::weather::WeatherModule,
+};
+
+/// Location and language of the cached weather.
+type Key = (String, String);
+
+/// Caches responses of the inner weather module per location.
+/// If the inner module fails, the last known weather is returned marked as stale,
+/// unless it's older than `max_stale`.
+#[derive(Debug)]
+pub struct CachedWeather<W: WeatherModule> {
+    inner: Arc<W>,
+    ttl: Duration,
+    max_stale: Duration,
+    cache: Mutex<HashMap<Key, (Instant, WeatherModel)>>,
+    /// Locks of keys, which are being fetched, so concurrent misses wait for one request
+    in_flight: Mutex<HashMap<Key, Arc<Mutex<()>>>>,
+}
+
+impl<W: WeatherModule> CachedWeather<W> {
+    #[tracing::instrument]
+    pub fn with_inner(inner: Arc<W>, ttl: Duration, max_stale: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            max_stale: max_stale.max(ttl),
+            cache: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn fresh(&self, key: &Key) -> Option<WeatherModel> {
+        self.cache
+            .lock()
+            .await
+            .get(key)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < self.ttl)
+            .map(|(_, weather)| weather.clone())
+    }
+
+    /// Fetches weather of the key, unless the request, which held its lock, did it.
+    /// Error is a string, because boxed error isn't `Send` and can't be held across await.
+    async fn fetch(&self, key: Key, location: &str, lang: &str) -> Result<WeatherModel, String> {
+        if let Some(weather) = self.fresh(&key).await {
+            return Ok(weather);
+        }
+
+        match self
+            .inner
+            .get_weather(location, lang)
+            .await
+            .map_err(|err| err.to_string())
+        {
+            Ok(weather) => {
+                // Locations of users, who left or moved, would stay forever otherwise
+                let mut cache = self.cache.lock().await;
+                cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < self.max_stale);
+                cache.insert(key, (Instant::now(), weather.clone()));
+                drop(cache);
+                Ok(weather)
+            }
+            Err(err) => {
+                let cached = self.cache.lock().await.get(&key).cloned();
+                cached.map_or_else(
+                    || Err(err),
+                    |(_, weather)| {
+                        tracing::warn!("Serving stale weather for {location}");
+                        Ok(WeatherModel {
+                            is_stale: true,
+                            ..weather
+                        })
+                    },
+                )
+            }
+        }
+    }
+}
+
+impl<W: WeatherModule> Create for CachedWeather<W> {
+    #[tracing::instrument]
+    fn new() -> Res<Arc<Self>> {
+        Ok(Arc::new(Self::with_inner(
+            W::new()?,
+            Duration::from_secs(CONFIG.weather_cache_ttl_secs),
+            Duration::from_secs(CONFIG.weather_max_stale_secs),
+        )))
+    }
+}
+
+#[async_trait::async_trait]
+impl<W: WeatherModule> WeatherModule for CachedWeather<W> {
+    #[tracing::instrument]
+    async fn get_weather(&self, location: &str, lang: &str) -> Res<WeatherModel> {
+        let key = (location.to_string(), lang.to_string());
+        if let Some(weather) = self.fresh(&key).await {
+            return Ok(weather);
+        }
+
+        let lock = self
+            .in_flight
+            .lock()
+            .await
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let guard = lock.lock().await;
+        let res = self.fetch(key.clone(), location, lang).await;
+        drop(guard);
+        {
+            // Nobody else waits for the lock, if only the map and this request hold it
+            let mut in_flight = self.in_flight.lock().await;
+            if Arc::strong_count(&lock) == 2 {
+                in_flight.remove(&key);
+            }
+        }
+        res.map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod cached_weather_tests {
+    use std::{sync::atomic::Ordering, time::Duration};
+
+    use crate::{
+        traits_mod::create_traits::Create,
+        weather_mod::{
+            cached_weather::CachedWeather, fake_weather::FakeWeather, weather::WeatherModule,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_cache_hit_within_ttl() {
+        let inner = FakeWeather::new().expect("Failed to create fake weather");
+        let cached =
+            CachedWeather::with_inner(inner.clone(), Duration::from_mins(1), Duration::MAX);
+
+        cached
+            .get_weather("Москва", "ru")
+            .await
+            .expect("Failed to get weather");
+        cached
+            .get_weather("Москва", "ru")
+            .await
+            .expect("Failed to get weather");
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+
+        let weather = cached
+            .get_weather("Казань", "ru")
+            .await
+            .expect("Failed to get weather");
+        assert_eq!(weather.status, "Казань");
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+
+        cached
+            .get_weather("Москва", "en")
+            .await
+            .expect("Failed to get weather");
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_misses_fetch_once() {
+        let inner = FakeWeather::new().expect("Failed to create fake weather");
+        let cached =
+            CachedWeather::with_inner(inner.clone(), Duration::from_mins(1), Duration::MAX);
+
+        let (first, second, third) = tokio::join!(
+            cached.get_weather("Москва", "ru"),
+            cached.get_weather("Москва", "ru"),
+            cached.get_weather("Москва", "ru"),
+        );
+        for weather in <[_; 3]>::from((first, second, third)) {
+            assert_eq!(weather.expect("Failed to get weather").status, "Москва");
+        }
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+        assert!(cached.in_flight.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cache_expired() {
+        let inner = FakeWeather::new().expect("Failed to create fake weather");
+        let cached = CachedWeather::with_inner(inner.clone(), Duration::ZERO, Duration::MAX);
+
+        cached
+            .get_weather("Москва", "ru")
+            .await
+            .expect("Failed to get weather");
+        cached
+            .get_weather("Москва", "ru")
+            .await
+            .expect("Failed to get weather");
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stale_while_error() {
+        let inner = FakeWeather::new().expect("Failed to create fake weather");
+        let cached = CachedWeather::with_inner(inner.clone(), Duration::ZERO, Duration::MAX);
+
+        let fresh = cached
+            .get_weather("Москва", "ru")
+            .await
+            .expect("Failed to get weather");
+        assert!(!fresh.is_stale);
+
+        inner.is_down.store(true, Ordering::SeqCst);
+        let stale = cached
+            .get_weather("Москва", "ru")
+            .await
+            .expect("Failed to get weather");
+        assert!(stale.is_stale);
+        assert_eq!(stale.status, "Москва");
+
+        assert!(cached.get_weather("Казань", "ru").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_evicted() {
+        let inner = FakeWeather::new().expect("Failed to create fake weather");
+        let cached = CachedWeather::with_inner(inner.clone(), Duration::ZERO, Duration::ZERO);
+
+        cached
+            .get_weather("Москва", "ru")
+            .await
+            .expect("Failed to get weather");
+        cached
+            .get_weather("Казань", "ru")
+            .await
+            .expect("Failed to get weather");
+        assert_eq!(cached.cache.lock().await.len(), 1);
+
+        // Weather of Moscow was evicted, so there is nothing stale to serve
+        inner.is_down.store(true, Ordering::SeqCst);
+        assert!(cached.get_weather("Москва", "ru").await.is_err());
+    }
+}
@@ -0,0 +1,46 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use crate::{
+    models_mod::weather_model::WeatherModel, traits_mod::create_traits::Create,
+    types_mod::result_types::Res, weather_mod::weather::WeatherModule,
+};
+
+/// Weather provider for tests, whose status is the requested location.
+#[derive(Debug, Default)]
+pub struct FakeWeather {
+    /// How many times weather was requested
+    pub calls: AtomicUsize,
+    /// Fails requests while set
+    pub is_down: AtomicBool,
+}
+
+impl Create for FakeWeather {
+    fn new() -> Res<Arc<Self>> {
+        Ok(Arc::new(Self::default()))
+    }
+}
+
+#[async_trait::async_trait]
+impl WeatherModule for FakeWeather {
+    async fn get_weather(&self, location: &str, _lang: &str) -> Res<WeatherModel> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        // Lets concurrent requests run, like a real request would
+        tokio::task::yield_now().await;
+        if self.is_down.load(Ordering::SeqCst) {
+            return Err("Provider is down".into());
+        }
+        Ok(WeatherModel {
+            temp_c: "1".into(),
+            feels_like_c: "1".into(),
+            wind_speed_kmph: "1".into(),
+            min_temp_c: "1".into(),
+            max_temp_c: "1".into(),
+            status: location.into(),
+            condition_code: None,
+            is_stale: false,
+        })
+    }
+}
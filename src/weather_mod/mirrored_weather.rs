@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use crate::{
+    models_mod::weather_model::WeatherModel,
+    tools_mod::config_tools::CONFIG,
+    traits_mod::create_traits::Create,
+    types_mod::result_types::Res,
+    weather_mod::{weather::WeatherModule, wttr_in_weather::WttrInWeather},
+};
+
+/// Tries `weather_url` and its mirrors in order and returns the first successful response.
+///
+/// It isn't a chain of different providers: all mirrors serve the same wttr.in API.
+#[derive(Debug)]
+pub struct MirroredWeather {
+    mirrors: Vec<Arc<dyn WeatherModule>>,
+}
+
+impl MirroredWeather {
+    #[tracing::instrument]
+    pub fn with_mirrors(mirrors: Vec<Arc<dyn WeatherModule>>) -> Self {
+        Self { mirrors }
+    }
+}
+
+impl Create for MirroredWeather {
+    #[tracing::instrument]
+    fn new() -> Res<Arc<Self>> {
+        let mirrors = std::iter::once(&CONFIG.weather_url)
+            .chain(&CONFIG.weather_mirror_urls)
+            .map(|url| Arc::new(WttrInWeather::with_url(url.clone())) as Arc<dyn WeatherModule>)
+            .collect();
+        Ok(Arc::new(Self::with_mirrors(mirrors)))
+    }
+}
+
+#[async_trait::async_trait]
+impl WeatherModule for MirroredWeather {
+    #[tracing::instrument]
+    async fn get_weather(&self, location: &str, lang: &str) -> Res<WeatherModel> {
+        let mut errors = vec![];
+        for mirror in &self.mirrors {
+            match mirror.get_weather(location, lang).await {
+                Ok(weather) => return Ok(weather),
+                Err(err) => {
+                    tracing::warn!("Weather mirror {mirror:?} failed: {err}");
+                    errors.push(err.to_string());
+                }
+            }
+        }
+        Err(format!("All weather mirrors failed: {}", errors.join("; ")).into())
+    }
+}
+
+#[cfg(test)]
+mod mirrored_weather_tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    };
+
+    use crate::weather_mod::{
+        fake_weather::FakeWeather, mirrored_weather::MirroredWeather, weather::WeatherModule,
+    };
+
+    fn mirror(is_down: bool) -> Arc<FakeWeather> {
+        Arc::new(FakeWeather {
+            is_down: AtomicBool::new(is_down),
+            ..FakeWeather::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_first_successful_mirror_wins() {
+        let mirrors = [mirror(true), mirror(false), mirror(false)];
+        let weather = MirroredWeather::with_mirrors(
+            mirrors
+                .iter()
+                .map(|mirror| mirror.clone() as Arc<dyn WeatherModule>)
+                .collect(),
+        );
+        let res = weather
+            .get_weather("Москва", "ru")
+            .await
+            .expect("Failed to get weather");
+        assert_eq!(res.status, "Москва");
+        let calls = mirrors
+            .iter()
+            .map(|mirror| mirror.calls.load(Ordering::SeqCst))
+            .collect::<Vec<_>>();
+        assert_eq!(calls, [1, 1, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_all_mirrors_failed() {
+        let weather = MirroredWeather::with_mirrors(vec![mirror(true), mirror(true)]);
+        assert!(weather.get_weather("Москва", "ru").await.is_err());
+        assert!(
+            MirroredWeather::with_mirrors(vec![])
+                .get_weather("Москва", "ru")
+                .await
+                .is_err()
+        );
+    }
+}
@@ -1,2 +1,6 @@
+pub mod cached_weather;
+#[cfg(test)]
+pub mod fake_weather;
+pub mod mirrored_weather;
 pub mod weather;
 pub mod wttr_in_weather;
@@ -7,5 +7,5 @@ use crate::{
 
 #[async_trait::async_trait]
 pub trait WeatherModule: Send + Sync + Create + Debug {
-    async fn get_weather(&self) -> Res<WeatherModel>;
+    async fn get_weather(&self, location: &str, lang: &str) -> Res<WeatherModel>;
 }
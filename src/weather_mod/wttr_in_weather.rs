@@ -1,11 +1,15 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
+use minijinja::context;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
 use reqwest::Client;
 use serde::Deserialize;
 
 use crate::{
-    models_mod::weather_model::WeatherModel, tools_mod::config_tools::CONFIG,
-    traits_mod::create_traits::Create, types_mod::result_types::Res,
+    models_mod::weather_model::WeatherModel,
+    tools_mod::{config_tools::CONFIG, template_tools::render_text},
+    traits_mod::create_traits::Create,
+    types_mod::result_types::Res,
     weather_mod::weather::WeatherModule,
 };
 
@@ -23,8 +27,13 @@ struct WttrInCondition {
     feels_like_c: String,
     #[serde(rename = "windspeedKmph")]
     wind_speed_kmph: String,
-    #[serde(rename = "lang_ru")]
+    #[serde(rename = "weatherDesc")]
     weather_desc: Vec<WttrInLangValue>,
+    /// Contains translated descriptions in `lang_<lang>` fields
+    #[serde(flatten)]
+    other: HashMap<String, serde_json::Value>,
+    #[serde(rename = "weatherCode")]
+    weather_code: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -43,29 +52,64 @@ struct WttrInLangValue {
 }
 
 #[derive(Debug)]
-pub struct WttrInWeather;
+pub struct WttrInWeather {
+    client: Client,
+    url: String,
+}
+
+impl WttrInWeather {
+    #[tracing::instrument]
+    pub fn with_url(url: String) -> Self {
+        Self {
+            client: Client::new(),
+            url,
+        }
+    }
+}
+
+/// Url of weather at the location, which may contain spaces, `&` or `/`, so it's encoded.
+#[tracing::instrument]
+fn weather_url(template: &str, location: &str) -> String {
+    let location = utf8_percent_encode(location, NON_ALPHANUMERIC).to_string();
+    render_text(template, context! { location })
+}
 
 impl Create for WttrInWeather {
     #[tracing::instrument]
     fn new() -> Res<Arc<Self>> {
-        Ok(Arc::new(Self))
+        Ok(Arc::new(Self::with_url(CONFIG.weather_url.clone())))
     }
 }
 
 #[async_trait::async_trait]
 impl WeatherModule for WttrInWeather {
     #[tracing::instrument]
-    async fn get_weather(&self) -> Res<WeatherModel> {
-        let client = Client::new();
-        let result = client
-            .get(CONFIG.weather_url.clone())
+    async fn get_weather(&self, location: &str, lang: &str) -> Res<WeatherModel> {
+        let result = self
+            .client
+            .get(weather_url(&self.url, location))
+            .query(&[("lang", lang)])
             .send()
             .await?
             .json::<WttrInWeatherResponse>()
             .await?;
-        let current = &result.current_condition[0];
-        let today = &result.weather[0];
-        let status = current.weather_desc.first().map_or("?", |v| &v.value);
+        let current = result
+            .current_condition
+            .first()
+            .ok_or("Weather response has no current condition")?;
+        let today = result
+            .weather
+            .first()
+            .ok_or("Weather response has no daily weather")?;
+        let translated = current
+            .other
+            .get(&format!("lang_{lang}"))
+            .and_then(|value| Vec::<WttrInLangValue>::deserialize(value).ok());
+        let status = translated
+            .as_ref()
+            .unwrap_or(&current.weather_desc)
+            .first()
+            .map_or("?", |v| &v.value);
         Ok(WeatherModel {
             temp_c: current.temp_c.clone(),
             feels_like_c: current.feels_like_c.clone(),
@@ -73,6 +117,26 @@ impl WeatherModule for WttrInWeather {
             min_temp_c: today.min_temp_c.clone(),
             max_temp_c: today.max_temp_c.clone(),
             status: status.to_string(),
+            condition_code: current.weather_code.parse().ok(),
+            is_stale: false,
         })
     }
 }
+
+#[cfg(test)]
+mod wttr_in_weather_tests {
+    use crate::weather_mod::wttr_in_weather::weather_url;
+
+    #[test]
+    fn test_weather_url_encodes_location() {
+        let template = "https://wttr.in/{{ location }}?format=j1";
+        assert_eq!(
+            weather_url(template, "Moscow"),
+            "https://wttr.in/Moscow?format=j1"
+        );
+        assert_eq!(
+            weather_url(template, "Нью Йорк&x=1/"),
+            "https://wttr.in/%D0%9D%D1%8C%D1%8E%20%D0%99%D0%BE%D1%80%D0%BA%26x%3D1%2F?format=j1"
+        );
+    }
+}